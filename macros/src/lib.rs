@@ -8,9 +8,9 @@ use syn::{ImplItem, ItemImpl, parse::Parser, parse_macro_input};
 
 use serialization::{
     AttributeArg, AttributeArgsList, AttributeValue, EXCLUDE_ATTRIBUTE, ImplBlock,
-    PY_BASE_ITERATOR, PY_DOUBLE_ENDED_ITERATOR, PY_EXACT_SIZE_ITERATOR,
-    PY_SIZED_DOUBLE_ENDED_ITERATOR, REGISTER_METHODS_ATTRIBUTE, SELF_GENERIC_ATTRIBUTE,
-    SERIALIZED_METHODS_PATH,
+    PY_ASYNC_ITERATOR, PY_BASE_ITERATOR, PY_DOUBLE_ENDED_ITERATOR, PY_EXACT_SIZE_ITERATOR,
+    PY_INFINITE_ITERATOR, PY_SIZED_DOUBLE_ENDED_ITERATOR, REGISTER_METHODS_ATTRIBUTE,
+    SELF_GENERIC_ATTRIBUTE, SERIALIZED_METHODS_PATH, UNBOUNDED_ATTRIBUTE,
 };
 
 #[proc_macro_attribute]
@@ -63,6 +63,8 @@ fn validate_selected_traits(attr: &TokenStream) -> Result<BTreeSet<String>, Stri
         String::from(PY_DOUBLE_ENDED_ITERATOR),
         String::from(PY_EXACT_SIZE_ITERATOR),
         String::from(PY_SIZED_DOUBLE_ENDED_ITERATOR),
+        String::from(PY_INFINITE_ITERATOR),
+        String::from(PY_ASYNC_ITERATOR),
     ]);
 
     let selected_traits: BTreeSet<_> = syn::parse2::<AttributeArgsList>(attr.clone().into())
@@ -115,54 +117,128 @@ pub fn return_literal(_attr: TokenStream, token_stream: TokenStream) -> TokenStr
     token_stream
 }
 
+#[proc_macro_attribute]
+pub fn fallible(_attr: TokenStream, token_stream: TokenStream) -> TokenStream {
+    token_stream
+}
+
+#[proc_macro_attribute]
+pub fn signature(_attr: TokenStream, token_stream: TokenStream) -> TokenStream {
+    token_stream
+}
+
 #[proc_macro_attribute]
 pub fn method_self_arg(_attr: TokenStream, token_stream: TokenStream) -> TokenStream {
     token_stream
 }
 
-fn parse_excluded_methods(attr: proc_macro2::TokenStream) -> Result<BTreeSet<String>, String> {
-    let excluded_methods = syn::parse2::<AttributeArgsList>(attr)
+/// Parses the methods listed under `key` in each trait group, e.g. `(Trait, exclude=(a, b))`
+fn parse_listed_methods(
+    attr: proc_macro2::TokenStream,
+    key: &str,
+) -> Result<BTreeMap<String, BTreeSet<String>>, String> {
+    let malformed = format!("Malformed {key} group");
+
+    syn::parse2::<AttributeArgsList>(attr)
         .map_err(|e| e.to_string())?
         .0
         .into_iter()
         .filter_map(|a| match a {
-            AttributeArg::Group(g) => g.content.0.into_iter().nth(1),
+            AttributeArg::Group(g) => {
+                let mut content = g.content.0.into_iter();
+                let Some(AttributeArg::Arg(trait_name)) = content.next() else {
+                    return None;
+                };
+                content.find_map(|a| match a {
+                    AttributeArg::KeyValueArg(kv) if kv.key == key => {
+                        Some((trait_name.to_string(), kv.value))
+                    }
+                    _ => None,
+                })
+            }
             _ => None,
         })
-        .filter_map(|a| {
-            if let AttributeArg::KeyValueArg(kv) = a {
-                Some(kv)
-            } else {
-                None
-            }
+        .map(|(trait_name, listed)| match listed {
+            AttributeValue::Group(g) => Ok((
+                trait_name,
+                g.content
+                    .0
+                    .into_iter()
+                    .map(|a| match a {
+                        AttributeArg::Arg(a) => Ok(a.to_string()),
+                        _ => Err(malformed.clone()),
+                    })
+                    .collect::<Result<_, _>>()?,
+            )),
+            AttributeValue::Ident(_) => Err(malformed.clone()),
         })
-        .find_map(|kv| {
-            if kv.key == EXCLUDE_ATTRIBUTE {
-                Some(kv.value)
-            } else {
-                None
-            }
-        });
+        .collect()
+}
 
-    let malformed = "Malformed exclude group";
+/// Checks that every async method sharing its name with a synchronous one also takes the same
+/// arguments, so the two sets of combinators don't drift apart
+fn check_async_counterparts(async_block: &ImplBlock, sync_block: &ImplBlock) -> Result<(), String> {
+    let arguments = |block: &ImplBlock, method: &serialization::Method| {
+        method
+            .args
+            .iter()
+            .filter(|a| a.expected_type != block.self_generic)
+            .map(|a| (a.name.clone(), a.expected_type.clone()))
+            .collect::<Vec<_>>()
+    };
 
-    match excluded_methods {
-        Some(AttributeValue::Group(g)) => g
-            .content
-            .0
-            .into_iter()
-            .map(|a| match a {
-                AttributeArg::Arg(a) => Ok(a.to_string()),
-                _ => Err(malformed.to_string()),
-            })
-            .collect(),
-        None => Ok(BTreeSet::new()),
-        _ => Err(malformed.to_string()),
+    for method in &async_block.methods {
+        let Some(counterpart) = sync_block.methods.iter().find(|m| m.name == method.name) else {
+            continue;
+        };
+
+        if arguments(async_block, method) != arguments(sync_block, counterpart)
+            || method.signature != counterpart.signature
+        {
+            return Err(format!(
+                "`{}` of {PY_ASYNC_ITERATOR} doesn't take the same arguments as in {PY_BASE_ITERATOR}",
+                method.name
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the methods registered on every iterator trait, keyed by the trait's name
+fn read_impl_blocks() -> Result<BTreeMap<String, ImplBlock>, String> {
+    let file = std::fs::File::open(SERIALIZED_METHODS_PATH)
+        .map_err(|fs_e| format!("Couldn't open serialized methods file ({fs_e})"))?;
+
+    serde_json::from_reader::<_, Vec<ImplBlock>>(file)
+        .map_err(|ser_e| format!("Couldn't deserialize from methods file ({ser_e})"))?
+        .into_iter()
+        .map(|ib| match ib.name.last() {
+            Some(name) => Ok((name.clone(), ib)),
+            None => Err("Impl block with empty name".to_string()),
+        })
+        .collect()
+}
+
+/// A method that raises `TypeError` whatever it's called with, in place of one that would have
+/// to exhaust an infinite iterator
+fn unbounded_stub(name: &str) -> syn::ImplItemFn {
+    let ident = syn::Ident::new(name, proc_macro2::Span::call_site());
+    let message = format!("cannot call {name}() on an infinite iterator");
+    syn::parse_quote! {
+        #[pyo3(signature = (*_args, **_kwargs))]
+        pub fn #ident(
+            &self,
+            _args: &::pyo3::Bound<'_, ::pyo3::types::PyTuple>,
+            _kwargs: ::std::option::Option<&::pyo3::Bound<'_, ::pyo3::types::PyDict>>,
+        ) -> ::pyo3::PyResult<()> {
+            ::std::result::Result::Err(::pyo3::exceptions::PyTypeError::new_err(#message))
+        }
     }
 }
 
 #[proc_macro_attribute]
-#[allow(clippy::too_many_lines)]
+#[allow(clippy::too_many_lines, clippy::missing_panics_doc)]
 pub fn add_trait_methods(attr: TokenStream, token_stream: TokenStream) -> TokenStream {
     let added_traits = match validate_selected_traits(&attr) {
         Ok(t) => t,
@@ -174,10 +250,9 @@ pub fn add_trait_methods(attr: TokenStream, token_stream: TokenStream) -> TokenS
         }
     };
 
-    let file = match std::fs::File::open(SERIALIZED_METHODS_PATH) {
-        Ok(f) => f,
-        Err(fs_e) => {
-            let e = format!("Couldn't open serialized methods file ({fs_e})",);
+    let trait_to_impl_block = match read_impl_blocks() {
+        Ok(map) => map,
+        Err(e) => {
             return quote! {
                 compile_error!(#e);
             }
@@ -185,24 +260,11 @@ pub fn add_trait_methods(attr: TokenStream, token_stream: TokenStream) -> TokenS
         }
     };
 
-    let trait_to_impl_block = match match serde_json::from_reader::<_, Vec<ImplBlock>>(file) {
-        Ok(d) => d,
-        Err(ser_e) => {
-            let e = format!("Couldn't deserialize from methods file ({ser_e})",);
-            return quote! {
-                compile_error!(#e);
-            }
-            .into();
-        }
-    }
-    .into_iter()
-    .map(|ib| match ib.name.last() {
-        Some(name) => Ok((name.clone(), ib)),
-        None => Err("Impl block with empty name".to_string()),
-    })
-    .collect::<Result<BTreeMap<_, _>, _>>()
-    {
-        Ok(map) => map,
+    let mut input = parse_macro_input!(token_stream as ItemImpl);
+
+    let attr: proc_macro2::TokenStream = attr.into();
+    let excluded_methods = match parse_listed_methods(attr.clone(), EXCLUDE_ATTRIBUTE) {
+        Ok(em) => em,
         Err(e) => {
             return quote! {
                 compile_error!(#e);
@@ -210,11 +272,9 @@ pub fn add_trait_methods(attr: TokenStream, token_stream: TokenStream) -> TokenS
             .into();
         }
     };
-
-    let mut input = parse_macro_input!(token_stream as ItemImpl);
-
-    let excluded_methods = match parse_excluded_methods(attr.into()) {
-        Ok(em) => em,
+    // methods that would have to exhaust an infinite iterator, which raise instead of hanging
+    let unbounded_methods = match parse_listed_methods(attr, UNBOUNDED_ATTRIBUTE) {
+        Ok(um) => um,
         Err(e) => {
             return quote! {
                 compile_error!(#e);
@@ -235,17 +295,56 @@ pub fn add_trait_methods(attr: TokenStream, token_stream: TokenStream) -> TokenS
         .into();
     };
 
+    let drift = match (
+        trait_to_impl_block.get(PY_ASYNC_ITERATOR),
+        trait_to_impl_block.get(PY_BASE_ITERATOR),
+    ) {
+        (Some(async_block), Some(sync_block)) if added_traits.contains(PY_ASYNC_ITERATOR) => {
+            check_async_counterparts(async_block, sync_block)
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = drift {
+        return quote! {
+            compile_error!(#e);
+        }
+        .into();
+    }
+
     for trait_name in &added_traits {
         let impl_block = trait_to_impl_block.get(trait_name).unwrap();
+
+        let unbounded = unbounded_methods.get(trait_name);
+        if let Some(missing) = unbounded
+            .into_iter()
+            .flatten()
+            .find(|name| !impl_block.methods.iter().any(|m| m.name == **name))
+        {
+            let e = format!("`{missing}` isn't a method of `{trait_name}`");
+            return quote! {
+                compile_error!(#e);
+            }
+            .into();
+        }
+
         for method in &impl_block.methods {
-            if method.name == impl_block.self_function || excluded_methods.contains(&method.name) {
+            if unbounded.is_some_and(|um| um.contains(&method.name)) {
+                input.items.push(ImplItem::Fn(unbounded_stub(&method.name)));
+                continue;
+            }
+
+            if method.name == impl_block.self_function
+                || excluded_methods
+                    .get(trait_name)
+                    .is_some_and(|em| em.contains(&method.name))
+            {
                 continue;
             }
 
             let mut impl_item = match method.into_impl_item(impl_block, &input_name) {
                 Ok(ii) => ii,
                 Err(e) => {
-                    let e = format!("Couldn't parse method ({e})",);
+                    let e = format!("Couldn't parse method ({e})");
                     return quote! {
                         compile_error!(#e);
                     }
@@ -259,7 +358,7 @@ pub fn add_trait_methods(attr: TokenStream, token_stream: TokenStream) -> TokenS
                 {
                     Ok(a) => a,
                     Err(e) => {
-                        let e = format!("Couldn't inject rename attribute ({e})",);
+                        let e = format!("Couldn't inject rename attribute ({e})");
                         return quote! {
                             compile_error!(#e);
                         }
@@ -287,3 +386,59 @@ pub fn add_trait_methods(attr: TokenStream, token_stream: TokenStream) -> TokenS
 
     quote!(#input).into()
 }
+
+/// Adds a method to a blueprint for every adapter registered on the given traits, recording the
+/// call along with the trait it needs and those it strips
+#[proc_macro_attribute]
+pub fn add_blueprint_methods(attr: TokenStream, token_stream: TokenStream) -> TokenStream {
+    let added_traits = match validate_selected_traits(&attr) {
+        Ok(t) => t,
+        Err(e) => {
+            return quote! {
+                compile_error!(#e);
+            }
+            .into();
+        }
+    };
+
+    let trait_to_impl_block = match read_impl_blocks() {
+        Ok(map) => map,
+        Err(e) => {
+            return quote! {
+                compile_error!(#e);
+            }
+            .into();
+        }
+    };
+
+    let mut input = parse_macro_input!(token_stream as ItemImpl);
+
+    for trait_name in &added_traits {
+        let Some(impl_block) = trait_to_impl_block.get(trait_name) else {
+            let e = format!("No methods registered for `{trait_name}`");
+            return quote! {
+                compile_error!(#e);
+            }
+            .into();
+        };
+
+        for method in &impl_block.methods {
+            if method.name == impl_block.self_function || !method.is_adapter() {
+                continue;
+            }
+
+            match method.into_blueprint_item(impl_block) {
+                Ok(impl_item) => input.items.push(ImplItem::Fn(impl_item)),
+                Err(e) => {
+                    let e = format!("Couldn't parse method ({e})");
+                    return quote! {
+                        compile_error!(#e);
+                    }
+                    .into();
+                }
+            }
+        }
+    }
+
+    quote!(#input).into()
+}
@@ -0,0 +1,76 @@
+"""Times element pipelines against their pure Python equivalents.
+
+Run with `python benchmarks/bench_pipeline.py [n]` after `maturin develop --release`.
+"""
+
+import statistics
+import sys
+import timeit
+from collections.abc import Callable
+from functools import reduce
+from itertools import islice
+from typing import Any
+
+from py_combinator import iterator_from
+
+# ruff: noqa: E731
+
+f = lambda x: x + 1
+p = lambda x: x % 3 != 0
+g = lambda t: t[0] + t[1]
+add = lambda a, x: a + x
+
+
+def cases(data: list[int]) -> dict[str, tuple[Callable[[], Any], Callable[[], Any]]]:
+    return {
+        "5 x map": (
+            lambda: iterator_from(data).map(f).map(f).map(f).map(f).map(f).to_list(),
+            lambda: list(map(f, map(f, map(f, map(f, map(f, data)))))),
+        ),
+        "20 x map": (
+            lambda: reduce(lambda it, _: it.map(f), range(20), iterator_from(data)).to_list(),
+            lambda: list(reduce(lambda it, _: map(f, it), range(20), data)),
+        ),
+        "map, filter, map": (
+            lambda: iterator_from(data).map(f).filter(p).map(f).to_list(),
+            lambda: list(map(f, filter(p, map(f, data)))),
+        ),
+        "enumerate, map": (
+            lambda: iterator_from(data).enumerate().map(g).to_list(),
+            lambda: list(map(g, enumerate(data))),
+        ),
+        "map, fold": (
+            lambda: iterator_from(data).map(f).fold(0, add),
+            lambda: reduce(add, map(f, data), 0),
+        ),
+        "map, take": (
+            lambda: iterator_from(data).map(f).take(len(data) // 10).to_list(),
+            lambda: list(islice(map(f, data), len(data) // 10)),
+        ),
+        "generator, 3 x map": (
+            lambda: iterator_from(x for x in data).map(f).map(f).map(f).to_list(),
+            lambda: list(map(f, map(f, map(f, (x for x in data))))),
+        ),
+    }
+
+
+def median_of(fn: Callable[[], Any], number: int = 3, repeat: int = 3) -> float:
+    return statistics.median(timeit.repeat(fn, number=number, repeat=repeat)) / number
+
+
+def main() -> None:
+    n = int(sys.argv[1]) if len(sys.argv) > 1 else 200_000
+    data = list(range(n))
+
+    print(f"{'case':<22}{'py-combinator':>15}{'pure Python':>15}{'ratio':>8}")
+    for name, (lib, native) in cases(data).items():
+        assert lib() == native()  # noqa: S101
+        lib_time, native_time = median_of(lib), median_of(native)
+        print(
+            f"{name:<22}{lib_time * 1000:>13.1f}ms{native_time * 1000:>13.1f}ms"
+            f"{lib_time / native_time:>8.2f}"
+        )
+
+
+if __name__ == "__main__":
+    main()
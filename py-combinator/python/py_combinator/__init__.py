@@ -1,4 +1,7 @@
 from py_combinator import _py_combinator as rs
+from py_combinator._py_combinator import (
+    PyAsyncIterator as AsyncIterator,
+)
 from py_combinator._py_combinator import (
     PyBaseIterator as BaseIterator,
 )
@@ -8,10 +11,26 @@ from py_combinator._py_combinator import (
 from py_combinator._py_combinator import (
     PyExactSizeIterator as ExactSizeIterator,
 )
+from py_combinator._py_combinator import (
+    PyInfiniteIterator as InfiniteIterator,
+)
+from py_combinator._py_combinator import (
+    PyPipeline as Pipeline,
+)
 from py_combinator._py_combinator import (
     PySizedDoubleEndedIterator as SizedDoubleEndedIterator,
 )
-from py_combinator._py_combinator import iterator_from
+from py_combinator._py_combinator import (
+    async_iterator_from,
+    count,
+    from_fn,
+    from_state,
+    iterate,
+    iterator_from,
+    range,
+    repeat,
+    repeat_with,
+)
 
 __doc__ = rs.__doc__
 
@@ -21,9 +40,20 @@ if hasattr(rs, "__all__"):
     __all__ += rs.__all__
 
 __all__ += [
+    "AsyncIterator",
     "BaseIterator",
     "DoubleEndedIterator",
     "ExactSizeIterator",
+    "InfiniteIterator",
+    "Pipeline",
     "SizedDoubleEndedIterator",
+    "async_iterator_from",
+    "count",
+    "from_fn",
+    "from_state",
+    "iterate",
     "iterator_from",
+    "range",
+    "repeat",
+    "repeat_with",
 ]
@@ -1,43 +1,596 @@
-from collections.abc import Callable, Iterable
+from collections import deque
+from collections.abc import (
+    AsyncIterable,
+    Awaitable,
+    Callable,
+    ItemsView,
+    Iterable,
+    KeysView,
+    ValuesView,
+)
+from types import TracebackType
+from typing import Any, Literal, overload
+
+# iterables whose length is known up front
+type SizedIterable[T] = list[T] | ExactSizeIterator[T] | SizedDoubleEndedIterator[T]
 
 class BaseIterator[T]:
+    def __iter__(self) -> BaseIterator[T]: ...
+    def __next__(self) -> T: ...
+    def __length_hint__(self) -> int: ...
+    def __enter__(self) -> BaseIterator[T]: ...
+    def __exit__(
+        self,
+        exc_type: type[BaseException] | None,
+        exc_value: BaseException | None,
+        traceback: TracebackType | None,
+    ) -> None: ...
+    def close(self) -> None: ...
+    def clone(self) -> BaseIterator[T]: ...
+    def __copy__(self) -> BaseIterator[T]: ...
+    def state(self) -> tuple[str, Any]: ...
+    def __reduce__(self) -> tuple[Callable[[tuple[str, Any]], BaseIterator[T]], tuple[tuple[str, Any]]]: ...
+    def size_hint(self) -> tuple[int, int | None]: ...
     def to_list(self) -> list[T]: ...
+    def to_tuple(self) -> tuple[T, ...]: ...
+    def to_set(self) -> set[T]: ...
+    def to_frozenset(self) -> frozenset[T]: ...
+    def to_dict[K, V](
+        self: BaseIterator[tuple[K, V]],
+        on_duplicate: Literal["last", "first", "error"] = "last",
+    ) -> dict[K, V]: ...
+    def to_deque(self, maxlen: int | None = None) -> deque[T]: ...
+    def join(self: BaseIterator[str], sep: str) -> str: ...
+    def to_bytes(self: BaseIterator[int]) -> bytes: ...
+    def collect[C](self, factory: Callable[[list[T]], C]) -> C: ...
     def fold(self, init: T, f: Callable[[T, T], T]) -> T: ...
+    def count(self) -> int: ...
     def map[U](self, f: Callable[[T], U]) -> BaseIterator[U]: ...
     def filter(self, f: Callable[[T], bool]) -> BaseIterator[T]: ...
+    def catch(
+        self,
+        exc_types: type[BaseException] | tuple[type[BaseException], ...],
+        handler: Callable[[BaseException], T] | None = None,
+    ) -> BaseIterator[T]: ...
+    def par_map[U](
+        self,
+        f: Callable[[T], U],
+        workers: int | None = None,
+        ordered: bool = True,
+        chunk_size: int = 64,
+    ) -> BaseIterator[U]: ...
     def enumerate(self) -> BaseIterator[tuple[int, T]]: ...
-    def take(self, n: int) -> SizedDoubleEndedIterator[T]: ...
+    # breaking: like every adapter, take leaves this iterator empty, where it used to consume
+    # only the first n elements and leave the rest
+    def take(self, n: int) -> BaseIterator[T]: ...
+    def skip(self, n: int) -> BaseIterator[T]: ...
+    def combinations(self, k: int) -> BaseIterator[tuple[T, ...]]: ...
+    def combinations_with_replacement(self, k: int) -> BaseIterator[tuple[T, ...]]: ...
+    def permutations(self, k: int | None = None) -> BaseIterator[tuple[T, ...]]: ...
+    def powerset(self) -> BaseIterator[tuple[T, ...]]: ...
+    def cartesian_product(self, *others: Iterable[Any]) -> BaseIterator[tuple[Any, ...]]: ...
+    @overload
+    def interleave[U](self, other: InfiniteIterator[U]) -> InfiniteIterator[T | U]: ...
+    @overload
+    def interleave[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    def interleave_shortest[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    def intersperse[U](self, sep: U) -> BaseIterator[T | U]: ...
+    def intersperse_with[U](self, f: Callable[[], U]) -> BaseIterator[T | U]: ...
+    @overload
+    def merge(
+        self, other: InfiniteIterator[T], key: Callable[[T], Any] | None = None
+    ) -> InfiniteIterator[T]: ...
+    @overload
+    def merge(self, other: Iterable[T], key: Callable[[T], Any] | None = None) -> BaseIterator[T]: ...
+    def kmerge(
+        self, *others: Iterable[T], key: Callable[[T], Any] | None = None
+    ) -> BaseIterator[T] | InfiniteIterator[T]: ...
+    def cycle(self) -> InfiniteIterator[T]: ...
+    def collect_errors(self) -> tuple[list[T], list[BaseException]]: ...
+    def partition(self, f: Callable[[T], bool]) -> tuple[list[T], list[T]]: ...
+    @overload
+    def unzip[A, B](self: BaseIterator[tuple[A, B]]) -> tuple[list[A], list[B]]: ...
+    @overload
+    def unzip(self) -> tuple[list[Any], ...]: ...
+    def tee(self, n: int) -> tuple[BaseIterator[T], ...]: ...
 
 class DoubleEndedIterator[T]:
+    def __iter__(self) -> DoubleEndedIterator[T]: ...
+    def __next__(self) -> T: ...
+    def __length_hint__(self) -> int: ...
+    def __enter__(self) -> DoubleEndedIterator[T]: ...
+    def __exit__(
+        self,
+        exc_type: type[BaseException] | None,
+        exc_value: BaseException | None,
+        traceback: TracebackType | None,
+    ) -> None: ...
+    def close(self) -> None: ...
+    def clone(self) -> DoubleEndedIterator[T]: ...
+    def __copy__(self) -> DoubleEndedIterator[T]: ...
+    def state(self) -> tuple[str, Any]: ...
+    def __reduce__(self) -> tuple[Callable[[tuple[str, Any]], DoubleEndedIterator[T]], tuple[tuple[str, Any]]]: ...
+    def size_hint(self) -> tuple[int, int | None]: ...
     def to_list(self) -> list[T]: ...
+    def to_tuple(self) -> tuple[T, ...]: ...
+    def to_set(self) -> set[T]: ...
+    def to_frozenset(self) -> frozenset[T]: ...
+    def to_dict[K, V](
+        self: DoubleEndedIterator[tuple[K, V]],
+        on_duplicate: Literal["last", "first", "error"] = "last",
+    ) -> dict[K, V]: ...
+    def to_deque(self, maxlen: int | None = None) -> deque[T]: ...
+    def join(self: DoubleEndedIterator[str], sep: str) -> str: ...
+    def to_bytes(self: DoubleEndedIterator[int]) -> bytes: ...
+    def collect[C](self, factory: Callable[[list[T]], C]) -> C: ...
     def fold(self, init: T, f: Callable[[T, T], T]) -> T: ...
+    def count(self) -> int: ...
     def map[U](self, f: Callable[[T], U]) -> DoubleEndedIterator[U]: ...
     def filter(self, f: Callable[[T], bool]) -> DoubleEndedIterator[T]: ...
-    def take(self, n: int) -> SizedDoubleEndedIterator[T]: ...
+    def catch(
+        self,
+        exc_types: type[BaseException] | tuple[type[BaseException], ...],
+        handler: Callable[[BaseException], T] | None = None,
+    ) -> DoubleEndedIterator[T]: ...
+    def par_map[U](
+        self,
+        f: Callable[[T], U],
+        workers: int | None = None,
+        ordered: bool = True,
+        chunk_size: int = 64,
+    ) -> BaseIterator[U]: ...
+    def take(self, n: int) -> DoubleEndedIterator[T]: ...
+    def skip(self, n: int) -> DoubleEndedIterator[T]: ...
+    def combinations(self, k: int) -> BaseIterator[tuple[T, ...]]: ...
+    def combinations_with_replacement(self, k: int) -> BaseIterator[tuple[T, ...]]: ...
+    def permutations(self, k: int | None = None) -> BaseIterator[tuple[T, ...]]: ...
+    def powerset(self) -> BaseIterator[tuple[T, ...]]: ...
+    def cartesian_product(self, *others: Iterable[Any]) -> BaseIterator[tuple[Any, ...]]: ...
+    @overload
+    def interleave[U](self, other: InfiniteIterator[U]) -> InfiniteIterator[T | U]: ...
+    @overload
+    def interleave[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    def interleave_shortest[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    def intersperse[U](self, sep: U) -> BaseIterator[T | U]: ...
+    def intersperse_with[U](self, f: Callable[[], U]) -> BaseIterator[T | U]: ...
+    @overload
+    def merge(
+        self, other: InfiniteIterator[T], key: Callable[[T], Any] | None = None
+    ) -> InfiniteIterator[T]: ...
+    @overload
+    def merge(self, other: Iterable[T], key: Callable[[T], Any] | None = None) -> BaseIterator[T]: ...
+    def kmerge(
+        self, *others: Iterable[T], key: Callable[[T], Any] | None = None
+    ) -> BaseIterator[T] | InfiniteIterator[T]: ...
+    def cycle(self) -> InfiniteIterator[T]: ...
+    def collect_errors(self) -> tuple[list[T], list[BaseException]]: ...
+    def partition(self, f: Callable[[T], bool]) -> tuple[list[T], list[T]]: ...
+    @overload
+    def unzip[A, B](self: DoubleEndedIterator[tuple[A, B]]) -> tuple[list[A], list[B]]: ...
+    @overload
+    def unzip(self) -> tuple[list[Any], ...]: ...
+    def tee(self, n: int) -> tuple[BaseIterator[T], ...]: ...
     def rev(self) -> DoubleEndedIterator[T]: ...
 
 class ExactSizeIterator[T]:
+    def __len__(self) -> int: ...
+    def __iter__(self) -> ExactSizeIterator[T]: ...
+    def __next__(self) -> T: ...
+    def __length_hint__(self) -> int: ...
+    def __enter__(self) -> ExactSizeIterator[T]: ...
+    def __exit__(
+        self,
+        exc_type: type[BaseException] | None,
+        exc_value: BaseException | None,
+        traceback: TracebackType | None,
+    ) -> None: ...
+    def close(self) -> None: ...
+    def clone(self) -> ExactSizeIterator[T]: ...
+    def __copy__(self) -> ExactSizeIterator[T]: ...
+    def state(self) -> tuple[str, Any]: ...
+    def __reduce__(self) -> tuple[Callable[[tuple[str, Any]], ExactSizeIterator[T]], tuple[tuple[str, Any]]]: ...
+    def size_hint(self) -> tuple[int, int | None]: ...
     def to_list(self) -> list[T]: ...
+    def to_tuple(self) -> tuple[T, ...]: ...
+    def to_set(self) -> set[T]: ...
+    def to_frozenset(self) -> frozenset[T]: ...
+    def to_dict[K, V](
+        self: ExactSizeIterator[tuple[K, V]],
+        on_duplicate: Literal["last", "first", "error"] = "last",
+    ) -> dict[K, V]: ...
+    def to_deque(self, maxlen: int | None = None) -> deque[T]: ...
+    def join(self: ExactSizeIterator[str], sep: str) -> str: ...
+    def to_bytes(self: ExactSizeIterator[int]) -> bytes: ...
+    def collect[C](self, factory: Callable[[list[T]], C]) -> C: ...
     def fold(self, init: T, f: Callable[[T, T], T]) -> T: ...
+    def count(self) -> int: ...
     def map[U](self, f: Callable[[T], U]) -> ExactSizeIterator[U]: ...
     def filter(self, f: Callable[[T], bool]) -> BaseIterator[T]: ...
+    def catch(
+        self,
+        exc_types: type[BaseException] | tuple[type[BaseException], ...],
+        handler: Callable[[BaseException], T] | None = None,
+    ) -> BaseIterator[T]: ...
+    def par_map[U](
+        self,
+        f: Callable[[T], U],
+        workers: int | None = None,
+        ordered: bool = True,
+        chunk_size: int = 64,
+    ) -> ExactSizeIterator[U]: ...
     def enumerate(self) -> ExactSizeIterator[tuple[int, T]]: ...
-    def take(self, n: int) -> SizedDoubleEndedIterator[T]: ...
+    def take(self, n: int) -> ExactSizeIterator[T]: ...
+    def skip(self, n: int) -> ExactSizeIterator[T]: ...
+    def combinations(self, k: int) -> ExactSizeIterator[tuple[T, ...]]: ...
+    def combinations_with_replacement(self, k: int) -> ExactSizeIterator[tuple[T, ...]]: ...
+    def permutations(self, k: int | None = None) -> ExactSizeIterator[tuple[T, ...]]: ...
+    def powerset(self) -> ExactSizeIterator[tuple[T, ...]]: ...
+    def cartesian_product(self, *others: Iterable[Any]) -> ExactSizeIterator[tuple[Any, ...]]: ...
+    @overload
+    def interleave[U](self, other: InfiniteIterator[U]) -> InfiniteIterator[T | U]: ...
+    @overload
+    def interleave[U](self, other: SizedIterable[U]) -> ExactSizeIterator[T | U]: ...
+    @overload
+    def interleave[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    @overload
+    def interleave_shortest[U](self, other: SizedIterable[U]) -> ExactSizeIterator[T | U]: ...
+    @overload
+    def interleave_shortest[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    def intersperse[U](self, sep: U) -> ExactSizeIterator[T | U]: ...
+    def intersperse_with[U](self, f: Callable[[], U]) -> ExactSizeIterator[T | U]: ...
+    @overload
+    def merge(
+        self, other: InfiniteIterator[T], key: Callable[[T], Any] | None = None
+    ) -> InfiniteIterator[T]: ...
+    @overload
+    def merge(
+        self, other: SizedIterable[T], key: Callable[[T], Any] | None = None
+    ) -> ExactSizeIterator[T]: ...
+    @overload
+    def merge(self, other: Iterable[T], key: Callable[[T], Any] | None = None) -> BaseIterator[T]: ...
+    @overload
+    def kmerge(
+        self, *others: SizedIterable[T], key: Callable[[T], Any] | None = None
+    ) -> ExactSizeIterator[T]: ...
+    @overload
+    def kmerge(
+        self, *others: Iterable[T], key: Callable[[T], Any] | None = None
+    ) -> BaseIterator[T] | InfiniteIterator[T]: ...
+    def cycle(self) -> InfiniteIterator[T]: ...
+    def collect_errors(self) -> tuple[list[T], list[BaseException]]: ...
+    def partition(self, f: Callable[[T], bool]) -> tuple[list[T], list[T]]: ...
+    @overload
+    def unzip[A, B](self: ExactSizeIterator[tuple[A, B]]) -> tuple[list[A], list[B]]: ...
+    @overload
+    def unzip(self) -> tuple[list[Any], ...]: ...
+    def tee(self, n: int) -> tuple[BaseIterator[T], ...]: ...
 
 class SizedDoubleEndedIterator[T]:
+    def __len__(self) -> int: ...
+    def __iter__(self) -> SizedDoubleEndedIterator[T]: ...
+    def __next__(self) -> T: ...
+    def __length_hint__(self) -> int: ...
+    def __enter__(self) -> SizedDoubleEndedIterator[T]: ...
+    def __exit__(
+        self,
+        exc_type: type[BaseException] | None,
+        exc_value: BaseException | None,
+        traceback: TracebackType | None,
+    ) -> None: ...
+    def close(self) -> None: ...
+    def clone(self) -> SizedDoubleEndedIterator[T]: ...
+    def __copy__(self) -> SizedDoubleEndedIterator[T]: ...
+    def state(self) -> tuple[str, Any]: ...
+    def __reduce__(self) -> tuple[Callable[[tuple[str, Any]], SizedDoubleEndedIterator[T]], tuple[tuple[str, Any]]]: ...
+    def size_hint(self) -> tuple[int, int | None]: ...
     def to_list(self) -> list[T]: ...
+    def to_tuple(self) -> tuple[T, ...]: ...
+    def to_set(self) -> set[T]: ...
+    def to_frozenset(self) -> frozenset[T]: ...
+    def to_dict[K, V](
+        self: SizedDoubleEndedIterator[tuple[K, V]],
+        on_duplicate: Literal["last", "first", "error"] = "last",
+    ) -> dict[K, V]: ...
+    def to_deque(self, maxlen: int | None = None) -> deque[T]: ...
+    def join(self: SizedDoubleEndedIterator[str], sep: str) -> str: ...
+    def to_bytes(self: SizedDoubleEndedIterator[int]) -> bytes: ...
+    def collect[C](self, factory: Callable[[list[T]], C]) -> C: ...
     def fold(self, init: T, f: Callable[[T, T], T]) -> T: ...
+    def count(self) -> int: ...
     def map[U](self, f: Callable[[T], U]) -> SizedDoubleEndedIterator[U]: ...
     def filter(self, f: Callable[[T], bool]) -> DoubleEndedIterator[T]: ...
+    def catch(
+        self,
+        exc_types: type[BaseException] | tuple[type[BaseException], ...],
+        handler: Callable[[BaseException], T] | None = None,
+    ) -> DoubleEndedIterator[T]: ...
+    def par_map[U](
+        self,
+        f: Callable[[T], U],
+        workers: int | None = None,
+        ordered: bool = True,
+        chunk_size: int = 64,
+    ) -> ExactSizeIterator[U]: ...
     def enumerate(self) -> SizedDoubleEndedIterator[tuple[int, T]]: ...
     def take(self, n: int) -> SizedDoubleEndedIterator[T]: ...
+    def skip(self, n: int) -> SizedDoubleEndedIterator[T]: ...
+    def combinations(self, k: int) -> ExactSizeIterator[tuple[T, ...]]: ...
+    def combinations_with_replacement(self, k: int) -> ExactSizeIterator[tuple[T, ...]]: ...
+    def permutations(self, k: int | None = None) -> ExactSizeIterator[tuple[T, ...]]: ...
+    def powerset(self) -> ExactSizeIterator[tuple[T, ...]]: ...
+    def cartesian_product(self, *others: Iterable[Any]) -> ExactSizeIterator[tuple[Any, ...]]: ...
+    @overload
+    def interleave[U](self, other: InfiniteIterator[U]) -> InfiniteIterator[T | U]: ...
+    @overload
+    def interleave[U](self, other: SizedIterable[U]) -> ExactSizeIterator[T | U]: ...
+    @overload
+    def interleave[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    @overload
+    def interleave_shortest[U](self, other: SizedIterable[U]) -> ExactSizeIterator[T | U]: ...
+    @overload
+    def interleave_shortest[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    def intersperse[U](self, sep: U) -> ExactSizeIterator[T | U]: ...
+    def intersperse_with[U](self, f: Callable[[], U]) -> ExactSizeIterator[T | U]: ...
+    @overload
+    def merge(
+        self, other: InfiniteIterator[T], key: Callable[[T], Any] | None = None
+    ) -> InfiniteIterator[T]: ...
+    @overload
+    def merge(
+        self, other: SizedIterable[T], key: Callable[[T], Any] | None = None
+    ) -> ExactSizeIterator[T]: ...
+    @overload
+    def merge(self, other: Iterable[T], key: Callable[[T], Any] | None = None) -> BaseIterator[T]: ...
+    @overload
+    def kmerge(
+        self, *others: SizedIterable[T], key: Callable[[T], Any] | None = None
+    ) -> ExactSizeIterator[T]: ...
+    @overload
+    def kmerge(
+        self, *others: Iterable[T], key: Callable[[T], Any] | None = None
+    ) -> BaseIterator[T] | InfiniteIterator[T]: ...
+    def cycle(self) -> InfiniteIterator[T]: ...
+    def collect_errors(self) -> tuple[list[T], list[BaseException]]: ...
+    def partition(self, f: Callable[[T], bool]) -> tuple[list[T], list[T]]: ...
+    @overload
+    def unzip[A, B](self: SizedDoubleEndedIterator[tuple[A, B]]) -> tuple[list[A], list[B]]: ...
+    @overload
+    def unzip(self) -> tuple[list[Any], ...]: ...
+    def tee(self, n: int) -> tuple[BaseIterator[T], ...]: ...
     def rev(self) -> SizedDoubleEndedIterator[T]: ...
 
+class InfiniteIterator[T]:
+    def __iter__(self) -> InfiniteIterator[T]: ...
+    def __next__(self) -> T: ...
+    def __length_hint__(self) -> int: ...
+    def __enter__(self) -> InfiniteIterator[T]: ...
+    def __exit__(
+        self,
+        exc_type: type[BaseException] | None,
+        exc_value: BaseException | None,
+        traceback: TracebackType | None,
+    ) -> None: ...
+    def close(self) -> None: ...
+    def clone(self) -> InfiniteIterator[T]: ...
+    def __copy__(self) -> InfiniteIterator[T]: ...
+    def state(self) -> tuple[str, Any]: ...
+    def __reduce__(self) -> tuple[Callable[[tuple[str, Any]], InfiniteIterator[T]], tuple[tuple[str, Any]]]: ...
+    def size_hint(self) -> tuple[int, int | None]: ...
+    def map[U](self, f: Callable[[T], U]) -> InfiniteIterator[U]: ...
+    def filter(self, f: Callable[[T], bool]) -> InfiniteIterator[T]: ...
+    def catch(
+        self,
+        exc_types: type[BaseException] | tuple[type[BaseException], ...],
+        handler: Callable[[BaseException], T] | None = None,
+    ) -> InfiniteIterator[T]: ...
+    def par_map[U](
+        self,
+        f: Callable[[T], U],
+        workers: int | None = None,
+        ordered: bool = True,
+        chunk_size: int = 64,
+    ) -> InfiniteIterator[U]: ...
+    def enumerate(self) -> InfiniteIterator[tuple[int, T]]: ...
+    def take(self, n: int) -> ExactSizeIterator[T] | BaseIterator[T]: ...
+    def skip(self, n: int) -> InfiniteIterator[T]: ...
+    def cycle(self) -> InfiniteIterator[T]: ...
+    def cartesian_product(self, *others: Iterable[Any]) -> InfiniteIterator[tuple[Any, ...]]: ...
+    def interleave[U](self, other: Iterable[U]) -> InfiniteIterator[T | U]: ...
+    @overload
+    def interleave_shortest[U](self, other: InfiniteIterator[U]) -> InfiniteIterator[T | U]: ...
+    @overload
+    def interleave_shortest[U](self, other: Iterable[U]) -> BaseIterator[T | U]: ...
+    def intersperse[U](self, sep: U) -> InfiniteIterator[T | U]: ...
+    def intersperse_with[U](self, f: Callable[[], U]) -> InfiniteIterator[T | U]: ...
+    def merge(self, other: Iterable[T], key: Callable[[T], Any] | None = None) -> InfiniteIterator[T]: ...
+    def kmerge(self, *others: Iterable[T], key: Callable[[T], Any] | None = None) -> InfiniteIterator[T]: ...
+    def tee(self, n: int) -> tuple[InfiniteIterator[T], ...]: ...
+
+class AsyncIterator[T]:
+    def __aiter__(self) -> AsyncIterator[T]: ...
+    def __anext__(self) -> Awaitable[T]: ...
+    def to_list(self) -> Awaitable[list[T]]: ...
+    def fold[U](self, init: U, f: Callable[[U, T], U | Awaitable[U]]) -> Awaitable[U]: ...
+    def map[U](self, f: Callable[[T], U | Awaitable[U]]) -> AsyncIterator[U]: ...
+    def amap[U](
+        self, f: Callable[[T], U | Awaitable[U]], concurrency: int, ordered: bool = True
+    ) -> AsyncIterator[U]: ...
+    def filter(self, f: Callable[[T], bool | Awaitable[bool]]) -> AsyncIterator[T]: ...
+    def enumerate(self) -> AsyncIterator[tuple[int, T]]: ...
+    def take(self, n: int) -> AsyncIterator[T]: ...
+
+class Pipeline[T, U]:
+    def __init__(self) -> None: ...
+    def __call__(
+        self, source: Iterable[T]
+    ) -> (
+        BaseIterator[U]
+        | DoubleEndedIterator[U]
+        | ExactSizeIterator[U]
+        | SizedDoubleEndedIterator[U]
+        | InfiniteIterator[U]
+    ): ...
+    def then[V](self, other: Pipeline[U, V]) -> Pipeline[T, V]: ...
+    def map[V](self, f: Callable[[U], V]) -> Pipeline[T, V]: ...
+    def filter(self, f: Callable[[U], bool]) -> Pipeline[T, U]: ...
+    def catch(
+        self,
+        exc_types: type[BaseException] | tuple[type[BaseException], ...],
+        handler: Callable[[BaseException], U] | None = None,
+    ) -> Pipeline[T, U]: ...
+    def par_map[V](
+        self,
+        f: Callable[[U], V],
+        workers: int | None = None,
+        ordered: bool = True,
+        chunk_size: int = 64,
+    ) -> Pipeline[T, V]: ...
+    def enumerate(self) -> Pipeline[T, tuple[int, U]]: ...
+    def take(self, n: int) -> Pipeline[T, U]: ...
+    def skip(self, n: int) -> Pipeline[T, U]: ...
+    def rev(self) -> Pipeline[T, U]: ...
+    def combinations(self, k: int) -> Pipeline[T, tuple[U, ...]]: ...
+    def combinations_with_replacement(self, k: int) -> Pipeline[T, tuple[U, ...]]: ...
+    def permutations(self, k: int | None = None) -> Pipeline[T, tuple[U, ...]]: ...
+    def powerset(self) -> Pipeline[T, tuple[U, ...]]: ...
+    def cartesian_product(self, *others: Iterable[Any]) -> Pipeline[T, tuple[Any, ...]]: ...
+    def interleave[V](self, other: Iterable[V]) -> Pipeline[T, U | V]: ...
+    def interleave_shortest[V](self, other: Iterable[V]) -> Pipeline[T, U | V]: ...
+    def intersperse[V](self, sep: V) -> Pipeline[T, U | V]: ...
+    def intersperse_with[V](self, f: Callable[[], V]) -> Pipeline[T, U | V]: ...
+    def merge(self, other: Iterable[U], key: Callable[[U], Any] | None = None) -> Pipeline[T, U]: ...
+    def kmerge(self, *others: Iterable[U], key: Callable[[U], Any] | None = None) -> Pipeline[T, U]: ...
+    def cycle(self) -> Pipeline[T, U]: ...
+
+@overload
+def iterator_from(
+    iterable: str,
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: None = None,
+) -> SizedDoubleEndedIterator[str]: ...
+@overload
+def iterator_from(
+    iterable: bytes | bytearray | memoryview,
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: None = None,
+) -> SizedDoubleEndedIterator[int]: ...
+@overload
+def iterator_from[T](
+    iterable: list[T] | tuple[T, ...],
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: None = None,
+) -> SizedDoubleEndedIterator[T]: ...
+@overload
+def iterator_from[K, V](
+    iterable: dict[K, V],
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: None = None,
+) -> SizedDoubleEndedIterator[K]: ...
+@overload
+def iterator_from[K](
+    iterable: KeysView[K],
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: None = None,
+) -> SizedDoubleEndedIterator[K]: ...
+@overload
+def iterator_from[V](
+    iterable: ValuesView[V],
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: None = None,
+) -> SizedDoubleEndedIterator[V]: ...
+@overload
+def iterator_from[K, V](
+    iterable: ItemsView[K, V],
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: None = None,
+) -> SizedDoubleEndedIterator[tuple[K, V]]: ...
+@overload
+def iterator_from[T](
+    iterable: set[T] | frozenset[T],
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: None = None,
+) -> ExactSizeIterator[T]: ...
+@overload
 def iterator_from[T](
     iterable: Iterable[T],
+    *,
+    snapshot: bool = False,
+    kind: Literal["base"],
+    length_hint: int | None = None,
+) -> BaseIterator[T]: ...
+@overload
+def iterator_from[T](
+    iterable: Iterable[T],
+    *,
+    snapshot: bool = False,
+    kind: Literal["exact_size"],
+    length_hint: int | None = None,
+) -> ExactSizeIterator[T]: ...
+@overload
+def iterator_from[T](
+    iterable: Iterable[T],
+    *,
+    snapshot: bool = False,
+    kind: Literal["double_ended"],
+    length_hint: int | None = None,
+) -> DoubleEndedIterator[T]: ...
+@overload
+def iterator_from[T](
+    iterable: Iterable[T],
+    *,
+    snapshot: bool = False,
+    kind: Literal["sized_double_ended"],
+    length_hint: int | None = None,
+) -> SizedDoubleEndedIterator[T]: ...
+@overload
+def iterator_from[T](
+    iterable: Iterable[T],
+    *,
+    snapshot: bool = False,
+    kind: Literal["auto"] = "auto",
+    length_hint: int | None = None,
 ) -> (
     BaseIterator[T]
     | DoubleEndedIterator[T]
     | ExactSizeIterator[T]
     | SizedDoubleEndedIterator[T]
+    | InfiniteIterator[T]
+): ...
+@overload
+def range(stop: int, /) -> SizedDoubleEndedIterator[int]: ...
+@overload
+def range(start: int, stop: int, step: int = 1, /) -> SizedDoubleEndedIterator[int]: ...
+def count(start: Any = 0, step: Any = 1) -> InfiniteIterator[Any]: ...
+@overload
+def repeat[T](x: T, n: int) -> SizedDoubleEndedIterator[T]: ...
+@overload
+def repeat[T](x: T, n: None = None) -> InfiniteIterator[T]: ...
+def repeat_with[T](f: Callable[[], T]) -> InfiniteIterator[T]: ...
+def iterate[T](init: T, f: Callable[[T], T]) -> InfiniteIterator[T]: ...
+def from_fn[T](f: Callable[[], T | None], sentinel: Any = None) -> BaseIterator[T]: ...
+def from_state(
+    state: tuple[str, Any],
+) -> (
+    BaseIterator[Any]
+    | DoubleEndedIterator[Any]
+    | ExactSizeIterator[Any]
+    | SizedDoubleEndedIterator[Any]
+    | InfiniteIterator[Any]
 ): ...
+def async_iterator_from[T](iterable: AsyncIterable[T]) -> AsyncIterator[T]: ...
@@ -0,0 +1,190 @@
+import asyncio
+import inspect
+from collections.abc import AsyncIterator as AbcAsyncIterator
+from typing import Any
+
+import pytest
+from py_combinator import AsyncIterator, BaseIterator, async_iterator_from
+
+# ruff: noqa: E731 S101
+
+
+async def agen(data: list[Any]) -> AbcAsyncIterator[Any]:
+    for x in data:
+        await asyncio.sleep(0)
+        yield x
+
+
+async def double(x: int) -> int:
+    await asyncio.sleep(0)
+    return x * 2
+
+
+async def is_even(x: int) -> bool:
+    await asyncio.sleep(0)
+    return x % 2 == 0
+
+
+class TestAsyncIterator:
+    def test_from_async_iterable(self) -> None:
+        it = async_iterator_from(agen([1, 2, 3]))
+        assert isinstance(it, AsyncIterator)
+        assert asyncio.run(it.to_list()) == [1, 2, 3]
+
+    def test_from_non_async_iterable_raises(self) -> None:
+        with pytest.raises(TypeError, match="not an async iterable"):
+            async_iterator_from([1, 2, 3])
+
+    def test_async_for(self) -> None:
+        async def collect() -> list[int]:
+            return [x async for x in async_iterator_from(agen([1, 2, 3]))]
+
+        assert asyncio.run(collect()) == [1, 2, 3]
+
+    def test_anext(self) -> None:
+        async def run() -> None:
+            it = async_iterator_from(agen([1]))
+            assert await anext(it) == 1
+            with pytest.raises(StopAsyncIteration):
+                await anext(it)
+
+        asyncio.run(run())
+
+    def test_sync_callbacks(self) -> None:
+        it = async_iterator_from(agen([1, 2, 3, 4]))
+        it = it.map(lambda x: x + 1).filter(lambda x: x % 2 == 0).enumerate()
+        assert asyncio.run(it.to_list()) == [(0, 2), (1, 4)]
+
+    def test_coroutine_callbacks(self) -> None:
+        it = async_iterator_from(agen([1, 2, 3, 4])).filter(is_even).map(double)
+        assert asyncio.run(it.to_list()) == [4, 8]
+
+    def test_take(self) -> None:
+        pulled = []
+
+        async def source() -> AbcAsyncIterator[int]:
+            for x in range(10):
+                pulled.append(x)
+                yield x
+
+        it = async_iterator_from(source()).filter(is_even).take(2).map(double)
+        assert asyncio.run(it.to_list()) == [0, 4]
+        assert pulled == [0, 1, 2]
+
+    def test_fold(self) -> None:
+        async def add(a: int, x: int) -> int:
+            await asyncio.sleep(0)
+            return a + x
+
+        assert asyncio.run(async_iterator_from(agen([1, 2, 3])).fold(0, add)) == 6
+        assert asyncio.run(async_iterator_from(agen([2, 4])).fold(1, lambda a, x: a * x)) == 8
+
+    def test_callback_raises(self) -> None:
+        async def run() -> None:
+            it = async_iterator_from(agen([1, 0, 2])).map(lambda x: 2 // x)
+            assert await anext(it) == 2
+            with pytest.raises(ZeroDivisionError):
+                await anext(it)
+            assert await anext(it) == 1
+
+        asyncio.run(run())
+
+    def test_cancellation(self) -> None:
+        async def run() -> None:
+            started = asyncio.Event()
+
+            async def slow(x: int) -> int:
+                started.set()
+                await asyncio.sleep(10)
+                return x
+
+            task = asyncio.ensure_future(async_iterator_from(agen([1])).map(slow).to_list())
+            await started.wait()
+            task.cancel()
+            with pytest.raises(asyncio.CancelledError):
+                await task
+
+        asyncio.run(run())
+
+    def test_amap(self) -> None:
+        running = 0
+        most_running = 0
+
+        async def slow_double(x: int) -> int:
+            nonlocal running, most_running
+            running += 1
+            most_running = max(most_running, running)
+            await asyncio.sleep(0.01 * (5 - x))
+            running -= 1
+            return x * 2
+
+        it = async_iterator_from(agen([1, 2, 3, 4])).amap(slow_double, concurrency=2)
+        assert asyncio.run(it.to_list()) == [2, 4, 6, 8]
+        assert most_running == 2
+
+    def test_amap_unordered(self) -> None:
+        async def delayed(x: int) -> int:
+            await asyncio.sleep(0.01 * x)
+            return x
+
+        it = async_iterator_from(agen([3, 1, 2])).amap(delayed, concurrency=3, ordered=False)
+        assert asyncio.run(it.to_list()) == [1, 2, 3]
+
+    def test_amap_sync_callback(self) -> None:
+        it = async_iterator_from(agen([1, 2, 3])).amap(lambda x: x + 1, concurrency=2)
+        assert asyncio.run(it.to_list()) == [2, 3, 4]
+
+    def test_amap_raises_cancels_in_flight(self) -> None:
+        cancelled = []
+
+        async def fail_on_zero(x: int) -> int:
+            try:
+                await asyncio.sleep(0 if x == 0 else 10)
+            except asyncio.CancelledError:
+                cancelled.append(x)
+                raise
+            return 1 // x
+
+        async def run() -> None:
+            it = async_iterator_from(agen([1, 2, 0])).amap(fail_on_zero, concurrency=3)
+            with pytest.raises(ZeroDivisionError):
+                await it.to_list()
+            await asyncio.sleep(0)
+
+        asyncio.run(run())
+        assert sorted(cancelled) == [1, 2]
+
+    def test_amap_stopping_early_cancels_in_flight(self) -> None:
+        cancelled = []
+
+        async def slow(x: int) -> int:
+            try:
+                await asyncio.sleep(0 if x == 0 else 10)
+            except asyncio.CancelledError:
+                cancelled.append(x)
+                raise
+            return x
+
+        async def source() -> AbcAsyncIterator[int]:
+            for x in range(3):
+                yield x
+
+        async def run() -> None:
+            it = async_iterator_from(source()).amap(slow, concurrency=3)
+            async for _ in it:
+                break
+            del it
+            await asyncio.sleep(0)
+
+        asyncio.run(run())
+        assert sorted(cancelled) == [1, 2]
+
+    def test_amap_invalid_concurrency(self) -> None:
+        with pytest.raises(ValueError, match="concurrency"):
+            async_iterator_from(agen([])).amap(double, concurrency=0)
+
+    def test_methods_match_sync_ones(self) -> None:
+        for name, method in inspect.getmembers(AsyncIterator, callable):
+            if name.startswith("_") or not hasattr(BaseIterator, name):
+                continue
+            assert method.__text_signature__ == getattr(BaseIterator, name).__text_signature__
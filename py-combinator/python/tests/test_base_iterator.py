@@ -1,6 +1,16 @@
+import threading
+import time
+from collections import Counter, deque
 from collections.abc import Callable, Iterable
 from copy import deepcopy
-from itertools import islice
+from operator import length_hint
+from itertools import (
+    combinations,
+    combinations_with_replacement,
+    islice,
+    permutations,
+    product,
+)
 from typing import Any
 
 import pytest
@@ -151,6 +161,19 @@ class TestBaseIterator:
         native_filter = list(filter(f, filter(f, nums)))
         assert lib_filter == native_filter
 
+    def test_filter_raises(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 0])
+        assert isinstance(it, expected_type)
+        with pytest.raises(ZeroDivisionError):
+            it.filter(lambda x: 1 / x).to_list()
+        it = iterator_creator([1, 0])
+        with pytest.raises(ZeroDivisionError):
+            it.map(lambda x: 1 / x).filter(lambda x: x > 0).to_list()
+
     def test_take(
         self,
         iterator_creator: Callable[[Iterable[Any]], IT_T],
@@ -163,7 +186,7 @@ class TestBaseIterator:
         lib_taken = lib_it.take(1)
         native_taken = islice(native_it, 1)
         assert lib_taken.to_list() == list(native_taken)
-        assert lib_it.to_list() == list(native_it)
+        assert lib_it.to_list() == []
 
     def test_take_2(
         self,
@@ -177,7 +200,7 @@ class TestBaseIterator:
         lib_taken = lib_it.take(2).to_list()
         native_taken = list(islice(native_it, 2))
         assert lib_taken == native_taken
-        assert lib_it.to_list() == list(native_it)
+        assert lib_it.to_list() == []
 
     def test_map_take(
         self,
@@ -192,3 +215,735 @@ class TestBaseIterator:
         lib_res = lib_it.map(f).take(3).to_list()
         native_res = list(islice(map(f, native_it), 3))
         assert lib_res == native_res
+
+    def test_take_is_lazy(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        pulled: list[int] = []
+        it = iterator_creator([1, 2, 3, 4])
+        assert isinstance(it, expected_type)
+        taken = it.map(lambda x: pulled.append(x) or x).take(2).map(lambda x: x * 10)
+        assert pulled == []
+        assert taken.to_list() == [10, 20]
+        assert pulled == [1, 2]
+
+    def test_skip(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        pulled: list[int] = []
+        it = iterator_creator([1, 2, 3, 4, 5])
+        assert isinstance(it, expected_type)
+        skipped = it.map(lambda x: pulled.append(x) or x).skip(2)
+        assert pulled == []
+        assert next(skipped) == 3
+        assert pulled == [1, 2, 3]
+        assert skipped.to_list() == [4, 5]
+        assert iterator_creator([1, 2]).skip(5).to_list() == []
+
+    def test_partition(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3, 4, 5]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        f = lambda x: x % 2 == 0
+        lib_partitioned = it.partition(f)
+        native_partitioned = (
+            [x for x in nums if f(x)],  # type: ignore[no-untyped-call]
+            [x for x in nums if not f(x)],  # type: ignore[no-untyped-call]
+        )
+        assert lib_partitioned == native_partitioned
+
+    def test_unzip(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        pairs = [(1, "a"), (2, "b"), (3, "c")]
+        it = iterator_creator(deepcopy(pairs))
+        assert isinstance(it, expected_type)
+        lib_unzipped = it.unzip()
+        native_unzipped = tuple(list(column) for column in zip(*pairs, strict=True))
+        assert lib_unzipped == native_unzipped
+
+    def test_unzip_triples(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        triples = [(1, "a", 1.0), (2, "b", 2.0)]
+        it = iterator_creator(deepcopy(triples))
+        assert isinstance(it, expected_type)
+        assert it.unzip() == ([1, 2], ["a", "b"], [1.0, 2.0])
+
+    def test_unzip_ragged(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([(1, 2), (3,)])
+        assert isinstance(it, expected_type)
+        with pytest.raises(ValueError, match="length"):
+            it.unzip()
+
+    def test_tee(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3, 4, 5]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        f = lambda x: x * 2
+        a, b, c = it.tee(3)
+        assert isinstance(a, BaseIterator)
+        assert [next(a), next(a)] == nums[:2]
+        assert b.map(f).to_list() == list(map(f, nums))
+        assert a.to_list() == nums[2:]
+        assert c.to_list() == nums
+
+    def test_to_tuple(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.to_tuple() == tuple(nums)
+
+    def test_to_set(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 2, 3, 1]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.to_set() == set(nums)
+
+    def test_to_frozenset(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 2, 3, 1]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        lib_frozen = it.to_frozenset()
+        assert isinstance(lib_frozen, frozenset)
+        assert lib_frozen == frozenset(nums)
+
+    def test_to_dict(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        pairs = [("a", 1), ("b", 2), ("a", 3)]
+        it = iterator_creator(deepcopy(pairs))
+        assert isinstance(it, expected_type)
+        assert it.to_dict() == dict(pairs)
+
+    def test_to_dict_first_wins(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        pairs = [("a", 1), ("b", 2), ("a", 3)]
+        it = iterator_creator(deepcopy(pairs))
+        assert isinstance(it, expected_type)
+        assert it.to_dict(on_duplicate="first") == dict(reversed(pairs))
+
+    def test_to_dict_duplicate_error(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        pairs = [("a", 1), ("b", 2), ("a", 3)]
+        it = iterator_creator(deepcopy(pairs))
+        assert isinstance(it, expected_type)
+        with pytest.raises(KeyError, match="a"):
+            it.to_dict(on_duplicate="error")
+
+    def test_bad_arguments_leave_iterator(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        pairs = [("a", 1), ("b", 2)]
+        it = iterator_creator(deepcopy(pairs))
+        assert isinstance(it, expected_type)
+        with pytest.raises(ValueError, match="on_duplicate must be one of"):
+            it.to_dict(on_duplicate="bogus")
+        with pytest.raises(TypeError, match="not iterable"):
+            it.interleave(1)
+        with pytest.raises(TypeError, match="not iterable"):
+            it.kmerge([1], 2)
+        with pytest.raises(TypeError, match="not callable"):
+            it.collect(1)
+        assert it.to_list() == pairs
+
+    def test_to_deque(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3, 4, 5]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        lib_deque = it.to_deque(maxlen=2)
+        native_deque = deque(nums, maxlen=2)
+        assert lib_deque == native_deque
+        assert lib_deque.maxlen == native_deque.maxlen
+
+    def test_join(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        words = ["a", "b", "c"]
+        it = iterator_creator(deepcopy(words))
+        assert isinstance(it, expected_type)
+        assert it.join(", ") == ", ".join(words)
+
+    def test_join_non_str(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator(["a", 1])
+        assert isinstance(it, expected_type)
+        with pytest.raises(TypeError, match="sequence item 1"):
+            it.join("")
+
+    def test_to_bytes(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [104, 105, 0, 255]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.to_bytes() == bytes(nums)
+
+    def test_to_bytes_out_of_range(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 256])
+        assert isinstance(it, expected_type)
+        with pytest.raises(ValueError, match="range"):
+            it.to_bytes()
+
+    def test_collect(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [3, 1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.collect(Counter) == Counter(nums)
+
+    def test_combinations(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3, 4]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.combinations(2).to_list() == list(combinations(nums, 2))
+
+    def test_combinations_with_replacement(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        lib_combinations = it.combinations_with_replacement(2).to_list()
+        native_combinations = list(combinations_with_replacement(nums, 2))
+        assert lib_combinations == native_combinations
+
+    def test_permutations(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.permutations().to_list() == list(permutations(nums))
+
+    def test_permutations_k(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3, 4]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.permutations(2).to_list() == list(permutations(nums, 2))
+
+    def test_powerset(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        native_powerset = [
+            subset for k in range(len(nums) + 1) for subset in combinations(nums, k)
+        ]
+        assert it.powerset().to_list() == native_powerset
+
+    def test_cartesian_product(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        lib_product = it.cartesian_product("ab", [True, False]).to_list()
+        native_product = list(product(nums, "ab", [True, False]))
+        assert lib_product == native_product
+
+    def test_interleave(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.interleave([4, 5]).to_list() == [1, 4, 2, 5, 3]
+        it = iterator_creator(deepcopy(nums))
+        lib_interleaved = it.interleave(iter([4, 5, 6, 7, 8])).to_list()
+        assert lib_interleaved == [1, 4, 2, 5, 3, 6, 7, 8]
+
+    def test_interleave_shortest(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.interleave_shortest([4, 5]).to_list() == [1, 4, 2, 5, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert it.interleave_shortest([4]).to_list() == [1, 4, 2]
+        it = iterator_creator(deepcopy(nums))
+        lib_interleaved = it.interleave_shortest(iter([4, 5, 6, 7])).to_list()
+        assert lib_interleaved == [1, 4, 2, 5, 3, 6]
+
+    def test_intersperse(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.intersperse(0).to_list() == [1, 0, 2, 0, 3]
+        assert iterator_creator([]).intersperse(0).to_list() == []
+
+    def test_intersperse_with(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = ["a", "b", "c"]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        separators = iter(range(10))
+        assert it.intersperse_with(lambda: next(separators)).to_list() == [
+            "a",
+            0,
+            "b",
+            1,
+            "c",
+        ]
+
+    def test_merge(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 3, 5, 7]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.merge(iter([2, 3, 4])).to_list() == [1, 2, 3, 3, 4, 5, 7]
+
+        pairs = [(1, "a"), (2, "a")]
+        it = iterator_creator(deepcopy(pairs))
+        merged = it.merge([(1, "b"), (2, "b")], key=lambda p: p[0]).to_list()
+        assert merged == [(1, "a"), (1, "b"), (2, "a"), (2, "b")]
+
+    def test_kmerge(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 4, 7]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        lib_merged = it.kmerge([2, 5, 8], iter([0, 3, 6, 9]), []).to_list()
+        assert lib_merged == sorted([*nums, 2, 5, 8, 0, 3, 6, 9])
+
+        words = ["ccc", "a"]
+        it = iterator_creator(deepcopy(words))
+        lib_merged = it.kmerge(["dd", "b"], key=lambda w: -len(w)).to_list()
+        assert lib_merged == ["ccc", "dd", "a", "b"]
+
+    def test_kmerge_incomparable(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2])
+        assert isinstance(it, expected_type)
+        with pytest.raises(TypeError):
+            it.kmerge(["a", "b"]).to_list()
+
+    def test_count(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.count() == len(nums)
+
+    def test_cycle(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.cycle().take(5).to_list() == [1, 2, 1, 2, 1]
+
+    def test_size_hint(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert it.size_hint()[0] == len(nums)
+        mapped = it.map(lambda x: x + 1).enumerate()
+        assert mapped.size_hint()[0] == len(nums)
+        lower, upper = mapped.filter(lambda x: x[1] > 2).size_hint()
+        assert lower == 0
+        assert upper in (None, len(nums))
+
+    def test_length_hint(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        assert length_hint(it) == len(nums)
+        next(it)
+        assert length_hint(it) == len(nums) - 1
+
+    def test_iteration(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = [1, 2, 3]
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        f = lambda x: x * 2
+        assert list(it.map(f)) == list(map(f, nums))
+
+    def test_iteration_raises(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 0])
+        assert isinstance(it, expected_type)
+        it = it.map(lambda x: 1 / x)
+        assert next(it) == 1
+        with pytest.raises(ZeroDivisionError):
+            next(it)
+
+    def test_stages_run_per_element(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2])
+        assert isinstance(it, expected_type)
+        calls = []
+        f = lambda x: calls.append(("f", x)) or x
+        p = lambda x: calls.append(("p", x)) or x > 1
+        g = lambda x: calls.append(("g", x)) or x
+        assert it.map(f).filter(p).map(g).to_list() == [2]
+        assert calls == [("f", 1), ("p", 1), ("f", 2), ("p", 2), ("g", 2)]
+
+    def test_extend_partially_consumed_chain(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2, 3, 4])
+        assert isinstance(it, expected_type)
+        it = it.map(lambda x: x * 2).enumerate()
+        assert next(it) == (0, 2)
+        assert it.map(lambda t: t[0] + t[1]).enumerate().to_list() == [
+            (0, 5),
+            (1, 8),
+            (2, 11),
+        ]
+
+    def test_par_map(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = list(range(50))
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        f = lambda x: x * 2
+        assert it.par_map(f, workers=3, chunk_size=4).to_list() == list(map(f, nums))
+
+    def test_par_map_unordered(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        nums = list(range(50))
+        it = iterator_creator(deepcopy(nums))
+        assert isinstance(it, expected_type)
+        f = lambda x: x * 2
+        lib_mapped = it.par_map(f, workers=3, ordered=False, chunk_size=4).to_list()
+        assert sorted(lib_mapped) == list(map(f, nums))
+
+    def test_par_map_runs_in_parallel(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2])
+        assert isinstance(it, expected_type)
+        # only returns if both elements are mapped at the same time
+        barrier = threading.Barrier(2, timeout=5)
+
+        def f(x: int) -> int:
+            barrier.wait()
+            return x
+
+        assert it.par_map(f, workers=2, chunk_size=1).to_list() == [1, 2]
+
+    def test_par_map_unordered_streams(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([0.5, 0, 0, 0])
+        assert isinstance(it, expected_type)
+        f = lambda x: time.sleep(x) or x
+        it = it.par_map(f, workers=2, ordered=False, chunk_size=1)
+        # the slow first element doesn't hold back those pulled after it
+        assert [next(it), next(it), next(it)] == [0, 0, 0]
+        assert it.to_list() == [0.5]
+
+    def test_par_map_keeps_its_threads(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator(range(200))
+        assert isinstance(it, expected_type)
+        threads = set()
+        f = lambda x: threads.add(threading.current_thread()) or x
+        assert it.par_map(f, workers=2, chunk_size=1).to_list() == list(range(200))
+        assert len(threads) <= 2
+
+    def test_par_map_raises_on_element(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2, 0, 4])
+        assert isinstance(it, expected_type)
+        it = it.par_map(lambda x: 4 // x, workers=2, chunk_size=1)
+        assert next(it) == 4
+        assert next(it) == 2
+        with pytest.raises(ZeroDivisionError):
+            next(it)
+        assert next(it) == 1
+
+    def test_par_map_invalid_arguments(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2])
+        assert isinstance(it, expected_type)
+        with pytest.raises(ValueError, match="workers"):
+            it.par_map(lambda x: x, workers=0)
+
+    def test_catch(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator(["1", "x", "3", "y"])
+        assert isinstance(it, expected_type)
+        assert it.map(lambda x: int(x)).catch(ValueError).map(lambda x: x * 2).to_list() == [2, 6]
+
+    def test_catch_with_handler(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator(["1", "x", "3"])
+        assert isinstance(it, expected_type)
+        handled = it.map(lambda x: int(x)).catch((TypeError, ValueError), lambda e: type(e).__name__)
+        assert handled.to_list() == [1, "ValueError", 3]
+
+    def test_catch_only_matching(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 0, 2])
+        assert isinstance(it, expected_type)
+        it = it.map(lambda x: 2 // x).catch(ValueError)
+        assert next(it) == 2
+        with pytest.raises(ZeroDivisionError):
+            next(it)
+        assert next(it) == 1
+
+    def test_catch_errors_from_filter(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 0, 2])
+        assert isinstance(it, expected_type)
+        filtered = it.filter(lambda x: 2 // x > 1).catch(ZeroDivisionError, lambda _: -1)
+        assert filtered.to_list() == [1, -1]
+
+    def test_catch_invalid_types(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1])
+        assert isinstance(it, expected_type)
+        with pytest.raises(TypeError, match="BaseException"):
+            it.catch(int)  # type: ignore[arg-type]
+
+    def test_collect_errors(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator(["1", "x", "3", "y"])
+        assert isinstance(it, expected_type)
+        values, errors = it.map(lambda x: int(x)).collect_errors()
+        assert values == [1, 3]
+        assert [type(e) for e in errors] == [ValueError, ValueError]
+        assert "'x'" in str(errors[0])
+
+    def test_error_notes_stage_and_element(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2, 3, 0])
+        assert isinstance(it, expected_type)
+        it = it.map(lambda x: x + 1).filter(lambda x: x > 2).map(lambda x: 3 // (x - 4))
+        assert next(it) == -3
+        with pytest.raises(ZeroDivisionError) as e:
+            next(it)
+        assert e.value.__notes__ == ["in map #2, on element 1: 4"]
+
+    def test_error_notes_truncate_element(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator(["x" * 100])
+        assert isinstance(it, expected_type)
+        with pytest.raises(ValueError) as e:
+            it.filter(lambda x: int(x)).to_list()
+        assert e.value.__notes__ == [f"in filter #1, on element 0: '{'x' * 79}..."]
+
+    def test_error_notes_terminals(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 0])
+        assert isinstance(it, expected_type)
+        with pytest.raises(ZeroDivisionError) as e:
+            it.fold(1, lambda a, x: a // x)
+        assert e.value.__notes__ == ["in fold, on element 1: 0"]
+
+    def test_error_notes_par_map(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2, 0])
+        assert isinstance(it, expected_type)
+        with pytest.raises(ZeroDivisionError) as e:
+            it.par_map(lambda x: 1 // x, workers=2, chunk_size=1).to_list()
+        assert e.value.__notes__ == ["in par_map #1, on element 2: 0"]
+
+    def test_error_notes_numbered_par_map(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 2, 3])
+        assert isinstance(it, expected_type)
+        it = it.par_map(lambda x: x - 2, workers=1).par_map(lambda x: 1 // x, workers=1)
+        with pytest.raises(ZeroDivisionError) as e:
+            it.to_list()
+        assert e.value.__notes__ == ["in par_map #2, on element 1: 0"]
+
+    def test_error_notes_kmerge_key(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator([1, 3])
+        assert isinstance(it, expected_type)
+        with pytest.raises(TypeError) as e:
+            it.merge([2, "x"], key=lambda x: x + 0).to_list()
+        assert e.value.__notes__ == ["in kmerge #1, on element 3: 'x'"]
+
+    def test_error_notes_intersperse_with(
+        self,
+        iterator_creator: Callable[[Iterable[Any]], IT_T],
+        expected_type: IT_T,
+    ) -> None:
+        it = iterator_creator(["a", "b", "c"])
+        assert isinstance(it, expected_type)
+        seps = iter([0])
+        with pytest.raises(StopIteration) as e:
+            it.intersperse_with(lambda: next(seps)).to_list()
+        assert e.value.__notes__ == ["in intersperse_with #1, on element 2: 'c'"]
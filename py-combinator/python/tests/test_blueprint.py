@@ -0,0 +1,117 @@
+import gc
+import weakref
+from typing import Any
+
+import pytest
+from py_combinator import (
+    BaseIterator,
+    DoubleEndedIterator,
+    InfiniteIterator,
+    Pipeline,
+    SizedDoubleEndedIterator,
+    count,
+    iterator_from,
+)
+
+# ruff: noqa: E731 S101
+
+
+class Marker:
+    pass
+
+
+class TestPipeline:
+    def test_applies_steps(self) -> None:
+        pipeline = Pipeline().map(lambda x: x * 2).filter(lambda x: x > 2).take(2)
+        assert pipeline([1, 2, 3, 4]).to_list() == [4, 6]
+
+    def test_reusable(self) -> None:
+        pipeline = Pipeline().map(lambda x: x + 1)
+        assert pipeline([1, 2]).to_list() == [2, 3]
+        assert pipeline(x for x in [3, 4]).to_list() == [4, 5]
+
+    def test_empty_pipeline(self) -> None:
+        it = Pipeline()([1, 2])
+        assert isinstance(it, SizedDoubleEndedIterator)
+        assert it.to_list() == [1, 2]
+
+    def test_keeps_source_capabilities(self) -> None:
+        pipeline = Pipeline().map(lambda x: x * 2)
+        assert isinstance(pipeline([1, 2, 3]), SizedDoubleEndedIterator)
+        assert isinstance(pipeline(x for x in [1]), BaseIterator)
+        assert isinstance(pipeline(count()), InfiniteIterator)
+        assert pipeline([1, 2, 3]).rev().to_list() == [6, 4, 2]
+
+    def test_strips_capabilities(self) -> None:
+        it = Pipeline().filter(lambda x: x % 2 == 1)([1, 2, 3])
+        assert isinstance(it, DoubleEndedIterator)
+        assert it.rev().to_list() == [3, 1]
+
+    def test_missing_capability(self) -> None:
+        pipeline = Pipeline().map(lambda x: x).rev()
+        assert pipeline([1, 2]).to_list() == [2, 1]
+        with pytest.raises(TypeError, match=r"cannot apply rev \(step 2\) to PyBaseIterator"):
+            pipeline(x for x in [1, 2])
+
+    def test_stripped_capability(self) -> None:
+        with pytest.raises(TypeError, match=r"cannot apply rev \(step 2\), as step 1 \(cycle\) strips"):
+            Pipeline().cycle().rev()
+        with pytest.raises(TypeError, match=r"as step 2 \(par_map\) strips"):
+            Pipeline().map(lambda x: x).par_map(lambda x: x).rev()
+        with pytest.raises(TypeError, match=r"cannot apply rev \(step 2\)"):
+            Pipeline().interleave([1]).then(Pipeline().rev())
+        assert Pipeline().filter(lambda x: x).rev()([0, 1, 2]).to_list() == [2, 1]
+
+    def test_only_adapters(self) -> None:
+        for terminal in ["to_list", "fold", "count", "collect", "tee", "partition", "take_inner"]:
+            assert not hasattr(Pipeline(), terminal)
+
+    def test_methods_return_new_blueprints(self) -> None:
+        base = Pipeline().map(lambda x: x + 1)
+        doubled = base.map(lambda x: x * 2)
+        assert base([1]).to_list() == [2]
+        assert doubled([1]).to_list() == [4]
+
+    def test_then(self) -> None:
+        double = Pipeline().map(lambda x: x * 2)
+        odd = Pipeline().filter(lambda x: x % 4 == 2)
+        assert double.then(odd)([1, 2, 3]).to_list() == [2, 6]
+        assert odd.then(double)([2, 3, 6]).to_list() == [4, 12]
+        assert Pipeline().then(double)([1]).to_list() == [2]
+
+    def test_arguments(self) -> None:
+        assert Pipeline().enumerate().catch(ValueError)(["a"]).to_list() == [(0, "a")]
+        assert Pipeline().combinations(2)([1, 2, 3]).to_list() == [(1, 2), (1, 3), (2, 3)]
+        assert Pipeline().permutations()([1, 2]).to_list() == [(1, 2), (2, 1)]
+        assert Pipeline().cartesian_product("ab")([1]).to_list() == [(1, "a"), (1, "b")]
+        assert Pipeline().intersperse(0)([1, 2]).to_list() == [1, 0, 2]
+        assert Pipeline().interleave([5, 6])([1, 2]).to_list() == [1, 5, 2, 6]
+        assert Pipeline().kmerge([2], [0], key=lambda x: -x)([3, 1]).to_list() == [3, 2, 1, 0]
+        assert Pipeline().par_map(lambda x: x + 1, workers=2, chunk_size=1)([1, 2]).to_list() == [2, 3]
+        assert Pipeline().cycle().take(3)([1, 2]).to_list() == [1, 2, 1]
+        assert Pipeline().merge([2], key=lambda x: x)([1, 3]).to_list() == [1, 2, 3]
+
+    def test_callback_errors(self) -> None:
+        pipeline = Pipeline().map(lambda x: 1 // x)
+        with pytest.raises(ZeroDivisionError):
+            pipeline([1, 0]).to_list()
+        assert pipeline([1]).to_list() == [1]
+
+    def test_consumes_iterator_sources(self) -> None:
+        source = iterator_from([1, 2, 3])
+        assert Pipeline().map(lambda x: -x)(source).to_list() == [-1, -2, -3]
+        assert source.to_list() == []
+
+    def test_invalid_source(self) -> None:
+        with pytest.raises(TypeError):
+            Pipeline().map(lambda x: x)(1)
+
+    def test_self_referential_blueprint_is_collected(self) -> None:
+        marker = Marker()
+        collected = weakref.ref(marker)
+        holder: dict[str, Any] = {"marker": marker}
+        holder["pipeline"] = Pipeline().map(lambda x: holder)
+
+        del marker, holder
+        gc.collect()
+        assert collected() is None
@@ -0,0 +1,100 @@
+import copy
+from collections.abc import Callable
+from typing import Any
+
+import pytest
+from py_combinator import (
+    DoubleEndedIterator,
+    InfiniteIterator,
+    SizedDoubleEndedIterator,
+    count,
+    iterator_from,
+    repeat,
+)
+from py_combinator import range as lib_range
+
+# ruff: noqa: E731 S101
+
+
+class TestCloning:
+    def test_clone_list_pipeline(self) -> None:
+        it = iterator_from([1, 2, 3, 4]).map(lambda x: x * 10).filter(lambda x: x != 20)
+        assert next(it) == 10
+        copied = it.clone()
+        assert isinstance(copied, DoubleEndedIterator)
+        assert it.to_list() == [30, 40]
+        assert copied.to_list() == [30, 40]
+
+    def test_copy_module(self) -> None:
+        it = iterator_from((1, 2, 3)).enumerate()
+        assert next(it) == (0, 1)
+        copied = copy.copy(it)
+        assert isinstance(copied, SizedDoubleEndedIterator)
+        assert len(copied) == 2
+        assert copied.rev().to_list() == [(2, 3), (1, 2)]
+        assert it.to_list() == [(1, 2), (2, 3)]
+
+    def test_clone_keeps_stage_state(self) -> None:
+        it = lib_range(10).enumerate().map(lambda t: t[0] * t[1])
+        for _ in range(3):
+            next(it)
+        assert it.clone().take(2).to_list() == [9, 16]
+        assert it.to_list()[:2] == [9, 16]
+
+    def test_clone_taken(self) -> None:
+        it = iterator_from([1, 2, 3]).filter(lambda x: x > 0).take(2)
+        copied = it.clone()
+        assert isinstance(copied, DoubleEndedIterator)
+        assert it.to_list() == copied.to_list() == [1, 2]
+
+        it = iterator_from([1, 2, 3]).filter(lambda x: x > 0).take(2).rev()
+        assert next(it) == 2
+        assert it.clone().to_list() == it.to_list() == [1]
+
+    def test_clone_infinite_sources(self) -> None:
+        it = count(5).map(lambda x: x * 2)
+        next(it)
+        copied = it.clone()
+        assert isinstance(copied, InfiniteIterator)
+        assert copied.take(2).to_list() == it.take(2).to_list() == [12, 14]
+        assert repeat("a").clone().take(2).to_list() == ["a", "a"]
+
+    def test_clone_rev(self) -> None:
+        it = iterator_from([1, 2, 3]).rev()
+        next(it)
+        assert it.clone().to_list() == [2, 1]
+        assert len(it) == 2
+
+    def test_clone_tee_branch(self) -> None:
+        a, _ = iterator_from(x for x in [1, 2, 3]).tee(2)
+        assert next(a) == 1
+        copied = a.clone()
+        assert a.to_list() == [2, 3]
+        assert copied.to_list() == [2, 3]
+
+    def test_clone_sees_list_changes(self) -> None:
+        nums = [1, 2, 3]
+        it = iterator_from(nums)
+        copied = it.clone()
+        nums[0] = 10
+        assert copied.to_list() == [10, 2, 3]
+
+    @pytest.mark.parametrize(
+        ("build", "stage"),
+        [
+            (lambda: iterator_from(x for x in [1]).map(lambda x: x), "a Python iterator"),
+            (lambda: iterator_from([1]).par_map(lambda x: x).map(lambda x: x), "par_map"),
+            (lambda: iterator_from({"a": 1}), "a dict or set"),
+            (lambda: iterator_from([1, 2]).combinations(2), "combinations"),
+            (lambda: iterator_from([1]).interleave([2]), "interleave"),
+            (lambda: iterator_from([1]).cycle(), "cycle"),
+        ],
+    )
+    def test_clone_blocked(self, build: Callable[[], Any], stage: str) -> None:
+        it = build()
+        with pytest.raises(TypeError, match=f"cannot clone an iterator built with {stage}"):
+            it.clone()
+        with pytest.raises(TypeError, match=stage):
+            copy.copy(it)
+        # the failed copy leaves the iterator as it was
+        assert next(it) is not None
@@ -0,0 +1,86 @@
+from collections.abc import Iterator
+
+import pytest
+from py_combinator import count, iterator_from
+
+# ruff: noqa: E731 S101
+
+
+class Tracked:
+    def __init__(self, n: int) -> None:
+        self.n = n
+        self.closed = False
+
+    def __iter__(self) -> Iterator[int]:
+        try:
+            yield from range(self.n)
+        finally:
+            self.closed = True
+
+
+class TestClosing:
+    def test_with_block_closes_generator(self) -> None:
+        source = Tracked(10)
+        with iterator_from(iter(source)) as it:
+            assert [next(it), next(it)] == [0, 1]
+            assert not source.closed
+        assert source.closed
+        assert it.to_list() == []
+
+    def test_with_block_passes_on_exceptions(self) -> None:
+        source = Tracked(10)
+        with pytest.raises(ZeroDivisionError), iterator_from(iter(source)) as it:
+            it.map(lambda x: 1 // x).to_list()
+        assert source.closed
+
+    def test_close_tears_down_adapters(self) -> None:
+        a, b = Tracked(10), Tracked(10)
+        it = (
+            iterator_from(iter(a))
+            .map(lambda x: x * 2)
+            .filter(lambda x: x > 0)
+            .interleave(iter(b))
+            .par_map(lambda x: x, workers=2, chunk_size=1)
+            .enumerate()
+        )
+        assert next(it) == (0, 2)
+        it.close()
+        assert a.closed
+        assert b.closed
+        assert next(it, None) is None
+
+    def test_close_merged_sources(self) -> None:
+        sources = [Tracked(3) for _ in range(3)]
+        it = iterator_from(iter(sources[0])).kmerge(*(iter(s) for s in sources[1:]))
+        assert next(it) == 0
+        it.close()
+        assert all(s.closed for s in sources)
+
+    def test_close_sized_iterator(self) -> None:
+        it = iterator_from([1, 2, 3]).map(lambda x: x + 1)
+        assert next(it) == 2
+        it.close()
+        it.close()
+        assert len(it) == 0
+        assert it.to_list() == []
+
+    def test_close_infinite_iterator(self) -> None:
+        with count().map(lambda x: x * 2) as it:
+            assert next(it) == 0
+        assert it.take(2).to_list() == []
+
+    def test_tee_closes_source_with_last_branch(self) -> None:
+        source = Tracked(10)
+        a, b = iterator_from(iter(source)).tee(2)
+        assert next(a) == 0
+        a.close()
+        assert not source.closed
+        assert b.take(2).to_list() == [0, 1]
+        b.close()
+        assert source.closed
+
+    def test_close_non_generator_source(self) -> None:
+        it = iterator_from(iter([1, 2, 3]))
+        assert next(it) == 1
+        it.close()
+        assert it.to_list() == []
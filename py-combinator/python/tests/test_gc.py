@@ -0,0 +1,116 @@
+import asyncio
+import gc
+import weakref
+from collections.abc import AsyncIterator, Callable, Iterator
+from typing import Any
+
+import pytest
+from py_combinator import async_iterator_from, iterator_from, repeat_with
+
+# ruff: noqa: E731 S101
+
+
+class Marker:
+    pass
+
+
+def gen() -> Iterator[int]:
+    yield from range(3)
+
+
+async def agen() -> AsyncIterator[int]:
+    for x in range(3):
+        yield x
+
+
+# each builds an iterator whose callback can reach it again through `holder`
+PIPELINES: list[Callable[[dict[str, Any]], Any]] = [
+    lambda holder: iterator_from([1, 2]).map(lambda x: (holder, x)),
+    lambda holder: iterator_from(gen()).filter(lambda x: holder is not None),
+    lambda holder: iterator_from([1]).map(lambda x: x).catch(ValueError, lambda e: holder),
+    lambda holder: iterator_from(range(3)).rev().intersperse_with(lambda: holder),
+    lambda holder: iterator_from([3, 1]).kmerge([2], key=lambda x: (holder, x)[1]),
+    lambda holder: iterator_from(gen()).par_map(lambda x: holder, workers=2).cycle(),
+    lambda holder: repeat_with(lambda: holder).enumerate(),
+    lambda holder: iterator_from([1, 2]).tee(1)[0].map(lambda x: holder),
+    lambda holder: async_iterator_from(agen()).map(lambda x: holder),
+    lambda holder: async_iterator_from(agen()).amap(lambda x: holder, concurrency=2),
+]
+
+
+class TestGarbageCollection:
+    @pytest.mark.parametrize("pipeline", PIPELINES)
+    def test_self_referential_pipeline_is_collected(
+        self, pipeline: Callable[[dict[str, Any]], Any]
+    ) -> None:
+        marker = Marker()
+        collected = weakref.ref(marker)
+        holder: dict[str, Any] = {"marker": marker}
+        holder["it"] = pipeline(holder)
+
+        del marker, holder
+        gc.collect()
+        assert collected() is None
+
+    def test_element_holding_iterator_is_collected(self) -> None:
+        marker = Marker()
+        collected = weakref.ref(marker)
+        elements: list[Any] = [marker]
+        it = iterator_from(elements).map(lambda x: x)
+        elements.append(it)
+
+        del marker, elements, it
+        gc.collect()
+        assert collected() is None
+
+    def test_tee_branches_in_a_cycle_are_collected(self) -> None:
+        marker = Marker()
+        collected = weakref.ref(marker)
+        holder: dict[str, Any] = {"marker": marker}
+        a, b = iterator_from([holder, 1]).tee(2)
+        assert next(a) is holder
+        holder["branches"] = (a, b)
+
+        del marker, holder, a, b
+        gc.collect()
+        assert collected() is None
+
+    def test_started_par_map_is_collected(self) -> None:
+        marker = Marker()
+        collected = weakref.ref(marker)
+        holder: dict[str, Any] = {"marker": marker}
+        f = lambda x: holder
+        f_collected = weakref.ref(f)
+        it = iterator_from(gen()).par_map(f, workers=2)
+        holder["it"] = it
+        # exhausted in place, which leaves the pool and its idle workers running
+        assert list(it) == [holder] * 3
+
+        # the workers share the iterator's reference to `f` rather than keeping their own
+        del marker, holder, f, it
+        gc.collect()
+        assert collected() is None
+        assert f_collected() is None
+
+    def test_reachable_iterator_is_kept(self) -> None:
+        holder: dict[str, Any] = {}
+        it = iterator_from([1, 2]).map(lambda x: holder)
+        holder["it"] = it
+        next(it)
+        gc.collect()
+        assert it.to_list() == [holder]
+
+    def test_started_async_pipeline_is_collected(self) -> None:
+        marker = Marker()
+        collected = weakref.ref(marker)
+
+        async def run() -> None:
+            holder: dict[str, Any] = {"marker": marker}
+            it = async_iterator_from(agen()).map(lambda x: holder)
+            holder["it"] = it
+            await anext(it)
+
+        asyncio.run(run())
+        del marker
+        gc.collect()
+        assert collected() is None
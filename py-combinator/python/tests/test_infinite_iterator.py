@@ -0,0 +1,131 @@
+from itertools import count as native_count
+from itertools import cycle as native_cycle
+from itertools import islice
+
+import pytest
+from py_combinator import (
+    BaseIterator,
+    InfiniteIterator,
+    SizedDoubleEndedIterator,
+    count,
+    from_fn,
+    iterate,
+    iterator_from,
+    repeat,
+    repeat_with,
+)
+
+# ruff: noqa: E731 S101
+
+
+class TestInfiniteIterator:
+    def test_count(self) -> None:
+        it = count()
+        assert isinstance(it, InfiniteIterator)
+        assert it.take(5).to_list() == list(islice(native_count(), 5))
+        assert count(10, 5).take(3).to_list() == [10, 15, 20]
+        assert count(0.5, 0.25).take(3).to_list() == [0.5, 0.75, 1.0]
+
+    def test_skip(self) -> None:
+        it = count().skip(3)
+        assert isinstance(it, InfiniteIterator)
+        assert it.take(2).to_list() == [3, 4]
+
+    def test_count_bad_step(self) -> None:
+        with pytest.raises(TypeError):
+            count("a", 1)
+
+    def test_repeat(self) -> None:
+        it = repeat("a", 3)
+        assert isinstance(it, SizedDoubleEndedIterator)
+        assert len(it) == 3
+        assert it.rev().to_list() == ["a", "a", "a"]
+
+        it = repeat("a")
+        assert isinstance(it, InfiniteIterator)
+        assert it.take(4).to_list() == ["a"] * 4
+
+    def test_repeat_with(self) -> None:
+        calls = iter(range(10))
+        it = repeat_with(lambda: next(calls))
+        assert isinstance(it, InfiniteIterator)
+        assert it.take(3).to_list() == [0, 1, 2]
+
+    def test_iterate(self) -> None:
+        it = iterate(1, lambda x: x * 2)
+        assert isinstance(it, InfiniteIterator)
+        assert it.take(5).to_list() == [1, 2, 4, 8, 16]
+
+    def test_from_fn(self) -> None:
+        lines = iter(["a", "b"])
+        it = from_fn(lambda: next(lines, None))
+        assert isinstance(it, BaseIterator)
+        assert it.to_list() == ["a", "b"]
+
+        nums = iter([3, 2, 1, 0, 5])
+        assert from_fn(lambda: next(nums), sentinel=0).to_list() == [3, 2, 1]
+
+    def test_cycle(self) -> None:
+        nums = [1, 2, 3]
+        it = iterator_from(iter(nums)).cycle()
+        assert isinstance(it, InfiniteIterator)
+        assert it.take(8).to_list() == list(islice(native_cycle(nums), 8))
+        assert iterator_from([]).cycle().take(3).to_list() == []
+
+    def test_combinators_stay_infinite(self) -> None:
+        it = count().map(lambda x: x * x)
+        assert isinstance(it, InfiniteIterator)
+        it = it.filter(lambda x: x % 2 == 0).enumerate()
+        assert isinstance(it, InfiniteIterator)
+        assert it.take(3).to_list() == [(0, 0), (1, 4), (2, 16)]
+        assert isinstance(iterator_from(count()), InfiniteIterator)
+
+    def test_interleave_shortest_is_finite(self) -> None:
+        it = count().interleave_shortest([10, 11])
+        assert isinstance(it, BaseIterator)
+        assert it.to_list() == [0, 10, 1, 11, 2]
+
+    def test_infinite_operands_stay_infinite(self) -> None:
+        its = [
+            iterator_from([1, 2]).interleave(count()),
+            iterator_from(x for x in [1, 2]).interleave(repeat(0)),
+            iterator_from([1, 2]).rev().merge(count()),
+            iterator_from([1, 2]).kmerge([3], count()),
+            iterator_from(x for x in [1]).kmerge(count()),
+            count().interleave_shortest(repeat(0)),
+            count().merge([1, 2]),
+            count().kmerge([1], [2]),
+        ]
+        for it in its:
+            assert isinstance(it, InfiniteIterator)
+            with pytest.raises(TypeError, match="infinite"):
+                it.to_list()
+        assert iterator_from([5, 6]).interleave(count()).take(5).to_list() == [5, 0, 6, 1, 2]
+        assert count(1).merge([1, 2]).take(4).to_list() == [1, 1, 2, 2]
+        assert isinstance(iterator_from([1]).interleave_shortest(count()), BaseIterator)
+
+    def test_tee_branches_stay_infinite(self) -> None:
+        a, b = count().tee(2)
+        assert isinstance(a, InfiniteIterator)
+        assert isinstance(b, InfiniteIterator)
+        assert a.take(2).to_list() == [0, 1]
+        with pytest.raises(TypeError, match="infinite"):
+            b.to_list()
+        assert b.take(3).to_list() == [0, 1, 2]
+
+    def test_terminals_raise(self) -> None:
+        it = count()
+        with pytest.raises(TypeError, match="infinite"):
+            it.to_list()
+        with pytest.raises(TypeError, match="infinite"):
+            it.count()
+        with pytest.raises(TypeError, match="infinite"):
+            it.fold(0, lambda a, x: a + x)
+        with pytest.raises(TypeError, match=r"collect_errors\(\) on an infinite"):
+            it.collect_errors()
+        assert it.take(2).to_list() == [0, 1]
+
+    def test_size_hint(self) -> None:
+        assert count().size_hint()[1] is None
+        assert count().filter(lambda x: x % 2).size_hint() == (0, None)
+        assert list(islice(count(), 3)) == [0, 1, 2]
@@ -1,5 +1,11 @@
+from collections import UserList, deque
+
+import pytest
 from py_combinator import (
     BaseIterator,
+    DoubleEndedIterator,
+    ExactSizeIterator,
+    SizedDoubleEndedIterator,
     iterator_from,
 )
 
@@ -16,3 +22,126 @@ class TestIteratorTypeDeduction:
         nums = (1, 2, 3, 4, 5)
         lib_it = iterator_from(iter(nums))
         assert isinstance(lib_it, BaseIterator)
+
+    def test_range_type_deduction(self) -> None:
+        lib_it = iterator_from(range(10**9))
+        assert isinstance(lib_it, SizedDoubleEndedIterator)
+
+    def test_huge_range_type_deduction(self) -> None:
+        lib_it = iterator_from(range(10**30))
+        assert isinstance(lib_it, BaseIterator)
+
+    def test_sequence_type_deduction(self) -> None:
+        sequences = [
+            (1, 2, 3),
+            "abc",
+            b"abc",
+            bytearray(b"abc"),
+            memoryview(b"abc"),
+        ]
+        for sequence in sequences:
+            lib_it = iterator_from(sequence)
+            assert isinstance(lib_it, SizedDoubleEndedIterator)
+
+    def test_slow_sequence_type_deduction(self) -> None:
+        for sequence in [deque([1, 2, 3]), UserList([1, 2, 3])]:
+            lib_it = iterator_from(sequence)
+            assert isinstance(lib_it, BaseIterator)
+            assert lib_it.to_list() == [1, 2, 3]
+
+    def test_dunder_sequence_type_deduction(self) -> None:
+        class Squares:
+            def __len__(self) -> int:
+                return 4
+
+            def __getitem__(self, i: int) -> int:
+                if i >= 4:
+                    raise IndexError(i)
+                return i * i
+
+        lib_it = iterator_from(Squares())
+        assert isinstance(lib_it, SizedDoubleEndedIterator)
+
+    def test_iterable_with_getitem_type_deduction(self) -> None:
+        class Labelled:
+            def __len__(self) -> int:
+                return 1
+
+            def __getitem__(self, key: str) -> int:
+                return 0
+
+            def __iter__(self):  # noqa: ANN204
+                return iter(["a"])
+
+        lib_it = iterator_from(Labelled())
+        assert isinstance(lib_it, BaseIterator)
+
+    def test_dict_type_deduction(self) -> None:
+        d = {"a": 1, "b": 2}
+        for container in [d, d.keys(), d.values(), d.items()]:
+            lib_it = iterator_from(container)
+            assert isinstance(lib_it, SizedDoubleEndedIterator)
+
+    def test_set_type_deduction(self) -> None:
+        for container in [{1, 2}, frozenset([1, 2])]:
+            lib_it = iterator_from(container)
+            assert isinstance(lib_it, ExactSizeIterator)
+
+    def test_explicit_kind(self) -> None:
+        nums = [1, 2, 3]
+        kinds = [
+            ("base", BaseIterator),
+            ("exact_size", ExactSizeIterator),
+            ("double_ended", DoubleEndedIterator),
+            ("sized_double_ended", SizedDoubleEndedIterator),
+            ("auto", SizedDoubleEndedIterator),
+        ]
+        for kind, expected_type in kinds:
+            lib_it = iterator_from(nums, kind=kind)
+            assert isinstance(lib_it, expected_type)
+            assert lib_it.to_list() == nums
+
+    def test_unknown_kind(self) -> None:
+        with pytest.raises(ValueError, match="kind must be one of"):
+            iterator_from([1], kind="sized")
+
+    def test_unavailable_kind(self) -> None:
+        gen = (x for x in range(3))
+        with pytest.raises(TypeError, match="without a length_hint"):
+            iterator_from(gen, kind="exact_size")
+        with pytest.raises(TypeError, match="from the back"):
+            iterator_from(gen, kind="double_ended")
+        with pytest.raises(TypeError, match="from the back"):
+            iterator_from({1, 2}, kind="sized_double_ended")
+
+    def test_unavailable_kind_keeps_iterator(self) -> None:
+        lib_it = iterator_from(iter([1, 2, 3]))
+        with pytest.raises(TypeError):
+            iterator_from(lib_it, kind="exact_size")
+        assert lib_it.to_list() == [1, 2, 3]
+
+    def test_length_hint(self) -> None:
+        gen = (x for x in range(3))
+        lib_it = iterator_from(gen, length_hint=3)
+        assert isinstance(lib_it, ExactSizeIterator)
+        assert len(lib_it) == 3
+        assert lib_it.to_list() == [0, 1, 2]
+
+        lib_it = iterator_from(
+            (x for x in range(3)), kind="exact_size", length_hint=2
+        )
+        assert lib_it.to_list() == [0, 1]
+
+    def test_length_hint_too_long(self) -> None:
+        lib_it = iterator_from((x for x in range(3)), length_hint=5)
+        with pytest.raises(ValueError, match="2 element"):
+            lib_it.to_list()
+
+    def test_huge_length_hint(self) -> None:
+        lib_it = iterator_from((x for x in range(2)), length_hint=10**12)
+        with pytest.raises(ValueError, match="short of its length_hint"):
+            lib_it.to_list()
+
+    def test_length_hint_mismatch(self) -> None:
+        with pytest.raises(ValueError, match="length_hint"):
+            iterator_from([1, 2], length_hint=3)
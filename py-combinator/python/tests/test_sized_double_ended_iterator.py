@@ -1,9 +1,17 @@
+import signal
 from copy import deepcopy
+from itertools import combinations, islice, permutations
 
+import pytest
 from py_combinator import (
+    BaseIterator,
+    DoubleEndedIterator,
+    ExactSizeIterator,
     SizedDoubleEndedIterator,
+    count,
     iterator_from,
 )
+from py_combinator import range as lib_range
 
 # ruff: noqa: E731 S101
 
@@ -32,8 +40,341 @@ class TestSizedDoubleEndedIterator:
         assert isinstance(it, SizedDoubleEndedIterator)
         assert it.enumerate().rev().to_list() == [(2, 3), (1, 2), (0, 1)]
 
+    def test_enumerate_both_ends(self) -> None:
+        it = iterator_from([1, 2, 3, 4]).map(lambda x: x * 10).enumerate()
+        assert next(it) == (0, 10)
+        it = it.map(lambda t: t[0]).filter(lambda i: i != 2).rev()
+        assert it.to_list() == [3, 1]
+
+    def test_stages_keep_kind(self) -> None:
+        it = iterator_from([1, 2, 3]).map(lambda x: x + 1).enumerate()
+        assert isinstance(it, SizedDoubleEndedIterator)
+        assert len(it) == 3
+        filtered = it.filter(lambda t: t[1] % 2 == 0)
+        assert isinstance(filtered, DoubleEndedIterator)
+        assert filtered.map(lambda t: t[1]).rev().to_list() == [4, 2]
+
+    def test_par_map_keeps_length(self) -> None:
+        it = iterator_from([1, 2, 3]).par_map(lambda x: x + 1, workers=2, chunk_size=1)
+        assert isinstance(it, ExactSizeIterator)
+        assert len(it) == 3
+        assert next(it) == 2
+        assert len(it) == 2
+        assert it.to_list() == [3, 4]
+
+    def test_catch_strips_length(self) -> None:
+        it = iterator_from(["1", "x", "3"]).map(lambda x: int(x)).catch(ValueError)
+        assert isinstance(it, DoubleEndedIterator)
+        assert it.rev().to_list() == [3, 1]
+
     def test_rev_enumerate(self) -> None:
         nums = [1, 2, 3]
         it = iterator_from(deepcopy(nums))
         assert isinstance(it, SizedDoubleEndedIterator)
         assert it.rev().enumerate().to_list() == [(0, 3), (1, 2), (2, 1)]
+
+    def test_combinatorics_len(self) -> None:
+        nums = [1, 2, 3, 4, 5]
+        it = iterator_from(deepcopy(nums))
+        assert isinstance(it, SizedDoubleEndedIterator)
+        lib_combinations = it.combinations(3)
+        assert isinstance(lib_combinations, ExactSizeIterator)
+        assert len(lib_combinations) == len(list(combinations(nums, 3)))
+        for _ in range(4):
+            next(lib_combinations)
+        assert len(lib_combinations) == len(list(combinations(nums, 3))) - 4
+
+    def test_permutations_len(self) -> None:
+        nums = [1, 2, 3, 4, 5]
+        it = iterator_from(deepcopy(nums)).permutations(2)
+        assert len(it) == len(list(permutations(nums, 2)))
+
+    def test_cartesian_product_len(self) -> None:
+        it = iterator_from([1, 2, 3]).cartesian_product("ab", range(4))
+        assert len(it) == 3 * 2 * 4
+        assert len(it.to_list()) == 3 * 2 * 4
+
+    def test_combinatorics_overflow(self) -> None:
+        it = iterator_from(list(range(100))).powerset()
+        with pytest.raises(OverflowError, match="too large to be counted"):
+            len(it)
+        with pytest.raises(OverflowError):
+            it.size_hint()
+        assert it.take(3).to_list() == [(), (0,), (1,)]
+
+        it = iterator_from(list(range(30))).permutations().map(lambda x: x[0])
+        with pytest.raises(OverflowError):
+            len(it)
+        assert next(it) == 0
+
+    def test_cartesian_product_overflow(self) -> None:
+        it = iterator_from(list(range(2**16))).cartesian_product(range(2**16), range(2**16), range(2**16))
+        with pytest.raises(OverflowError):
+            len(it)
+        assert next(it) == (0, 0, 0, 0)
+
+    def test_cartesian_product_with_infinite_iterator(self) -> None:
+        with pytest.raises(TypeError, match="infinite iterator"):
+            iterator_from([1]).cartesian_product(count())
+
+    def test_interleave_len(self) -> None:
+        it = iterator_from([1, 2, 3]).interleave([4, 5])
+        assert isinstance(it, ExactSizeIterator)
+        assert len(it) == 5
+        next(it)
+        next(it)
+        assert len(it) == 3
+        assert isinstance(iterator_from([1]).interleave(iter([2])), BaseIterator)
+
+    def test_interleave_shortest_len(self) -> None:
+        for a, b in [(3, 2), (2, 3), (3, 3), (0, 2), (2, 0)]:
+            it = iterator_from(list(range(a))).interleave_shortest(list(range(b)))
+            assert len(it) == len(it.to_list())
+
+    def test_intersperse_len(self) -> None:
+        it = iterator_from([1, 2, 3]).intersperse(0)
+        assert isinstance(it, ExactSizeIterator)
+        assert len(it) == 5
+        next(it)
+        next(it)
+        assert len(it) == 3
+        assert len(iterator_from([]).intersperse(0)) == 0
+
+    def test_kmerge_len(self) -> None:
+        it = iterator_from([1, 4]).kmerge([2, 5], [3])
+        assert isinstance(it, ExactSizeIterator)
+        assert len(it) == 5
+        for _ in range(3):
+            next(it)
+        assert len(it) == 2
+        assert isinstance(iterator_from([1]).merge(iter([2])), BaseIterator)
+
+    def test_range(self) -> None:
+        for bounds in [(10,), (3, 10), (10, 3, -2), (0, 10, 3), (5, 5), (5, 0)]:
+            it = lib_range(*bounds)
+            assert isinstance(it, SizedDoubleEndedIterator)
+            assert len(it) == len(range(*bounds))
+            assert it.to_list() == list(range(*bounds))
+            assert lib_range(*bounds).rev().to_list() == list(reversed(range(*bounds)))
+
+    def test_range_len_is_constant_time(self) -> None:
+        it = iterator_from(range(0, 10**18, 7))
+        assert len(it) == len(range(0, 10**18, 7))
+        native_tail = list(islice(reversed(range(0, 10**18, 7)), 2))
+        assert it.rev().take(2).to_list() == native_tail
+
+    def test_range_big_bounds(self) -> None:
+        bounds = (2**70, 2**70 + 10, 3)
+        it = iterator_from(range(*bounds))
+        assert len(it) == len(range(*bounds))
+        assert it.rev().to_list() == list(reversed(range(*bounds)))
+
+    def test_range_extreme_i64_bounds(self) -> None:
+        bounds = (-(2**63), 2**63 - 1, 2**62)
+        assert lib_range(*bounds).to_list() == list(range(*bounds))
+
+    def test_range_zero_step(self) -> None:
+        with pytest.raises(ValueError, match="zero"):
+            lib_range(0, 10, 0)
+
+    @pytest.mark.skipif(not hasattr(signal, "setitimer"), reason="needs interval timers")
+    def test_interrupting_terminal(self) -> None:
+        # the alarm arrives while the terminal holds the GIL, as a Ctrl+C would
+        previous = signal.signal(signal.SIGALRM, signal.default_int_handler)
+        try:
+            it = lib_range(0, 2**62)
+            signal.setitimer(signal.ITIMER_REAL, 0.1)
+            with pytest.raises(KeyboardInterrupt):
+                it.count()
+        finally:
+            signal.setitimer(signal.ITIMER_REAL, 0)
+            signal.signal(signal.SIGALRM, previous)
+        # the terminal consumed the iterator
+        assert it.to_list() == []
+
+    def test_sequences(self) -> None:
+        sequences = [
+            (1, 2, 3),
+            "h\u00e9llo \U0001f600",
+            b"abc",
+            bytearray(b"abc"),
+            memoryview(b"abc"),
+            memoryview(bytearray(b"abc")).cast("c"),
+        ]
+        for sequence in sequences:
+            it = iterator_from(sequence)
+            assert len(it) == len(sequence)
+            assert it.to_list() == list(sequence)
+            assert iterator_from(sequence).rev().to_list() == list(reversed(sequence))
+
+    def test_dunder_sequence(self) -> None:
+        class Squares:
+            def __len__(self) -> int:
+                return 4
+
+            def __getitem__(self, i: int) -> int:
+                if i >= 4:
+                    raise IndexError(i)
+                return i * i
+
+        it = iterator_from(Squares())
+        assert len(it) == 4
+        assert it.rev().to_list() == [9, 4, 1, 0]
+
+    def test_dict(self) -> None:
+        d = {"a": 1, "b": 2, "c": 3}
+        for container in [d, d.keys(), d.values(), d.items()]:
+            it = iterator_from(container)
+            assert len(it) == len(d)
+            assert it.to_list() == list(container)
+            assert iterator_from(container).rev().to_list() == list(reversed(container))
+
+    def test_take_both_ends(self) -> None:
+        it = iterator_from([1, 2, 3, 4, 5]).map(lambda x: x * 10).take(3)
+        assert isinstance(it, SizedDoubleEndedIterator)
+        assert len(it) == 3
+        assert it.rev().to_list() == [30, 20, 10]
+
+        it = lib_range(10).enumerate().take(4)
+        assert next(it) == (0, 0)
+        assert it.rev().to_list() == [(3, 3), (2, 2), (1, 1)]
+        # a filter after a take keeps the iterator double-ended
+        it = lib_range(10).take(3).filter(lambda x: x != 1)
+        assert it.rev().to_list() == [2, 0]
+
+    def test_take_skip_unsized_both_ends(self) -> None:
+        is_even = lambda x: x % 2 == 0
+        it = iterator_from([1, 2, 3, 4, 5, 6, 7, 8]).filter(is_even).take(2)
+        assert type(it) is DoubleEndedIterator
+        assert it.rev().to_list() == [4, 2]
+
+        it = iterator_from([1, 2, 3, 4, 5, 6, 7, 8]).filter(is_even).skip(1)
+        assert type(it) is DoubleEndedIterator
+        assert it.rev().to_list() == [8, 6, 4]
+
+        it = iterator_from(range(10)).filter(is_even).skip(1).take(3)
+        assert next(it) == 2
+        assert it.rev().to_list() == [6, 4]
+
+        # stages added after pulling from the back apply to the elements pulled ahead too
+        it = iterator_from([1, 2, 3, 4]).filter(is_even).take(2).rev()
+        assert next(it) == 4
+        assert it.rev().map(lambda x: x * 10).to_list() == [20]
+        assert iterator_from([1, 2]).filter(is_even).take(5).rev().to_list() == [2]
+
+    def test_skip_both_ends(self) -> None:
+        it = lib_range(6).skip(2)
+        assert isinstance(it, SizedDoubleEndedIterator)
+        assert len(it) == 4
+        it = it.rev()
+        assert next(it) == 5
+        assert it.rev().to_list() == [2, 3, 4]
+        assert lib_range(6).skip(2).rev().to_list() == [5, 4, 3, 2]
+        assert lib_range(10).skip(2).take(3).rev().to_list() == [4, 3, 2]
+        assert lib_range(10).take(5).skip(2).rev().to_list() == [4, 3, 2]
+        assert lib_range(5).skip(2).enumerate().rev().to_list() == [(2, 4), (1, 3), (0, 2)]
+        assert lib_range(5).enumerate().skip(3).rev().to_list() == [(4, 4), (3, 3)]
+        assert len(iterator_from([1, 2]).take(5)) == 2
+        assert len(count().take(3)) == 3
+
+    def test_dict_both_ends(self) -> None:
+        d = {"a": 1, "b": 2, "c": 3}
+        it = iterator_from(d)
+        assert next(it) == "a"
+        assert len(it) == 2
+        assert it.rev().to_list() == ["c", "b"]
+
+    def test_dict_changed_size(self) -> None:
+        d = {"a": 1, "b": 2}
+        it = iterator_from(d).map(lambda k: d.setdefault(k * 2, 0))
+        with pytest.raises(RuntimeError, match="dictionary changed size"):
+            it.to_list()
+
+    def test_set(self) -> None:
+        s = {1, 2, 3}
+        it = iterator_from(s)
+        assert isinstance(it, ExactSizeIterator)
+        assert len(it) == len(s)
+        assert it.to_set() == s
+
+    def test_set_changed_size(self) -> None:
+        s = {1, 2, 3}
+        it = iterator_from(s).map(lambda x: s.discard(x))
+        with pytest.raises(RuntimeError, match="Set changed size"):
+            it.to_list()
+
+    def test_list_changed_size(self) -> None:
+        nums = [1, 2, 3]
+        it = iterator_from(nums).map(lambda x: nums.append(x))
+        with pytest.raises(RuntimeError, match="list changed size"):
+            it.to_list()
+
+        nums = [1, 2, 3]
+        it = iterator_from(nums).map(lambda _: nums.pop())
+        with pytest.raises(RuntimeError, match="list changed size"):
+            it.to_list()
+
+    def test_list_changed_size_finishes(self) -> None:
+        nums = [1, 2, 3]
+        it = iterator_from(nums)
+        assert next(it) == 1
+        nums.append(4)
+        with pytest.raises(RuntimeError, match="list changed size"):
+            next(it)
+        with pytest.raises(StopIteration):
+            next(it)
+        assert len(it) == 0
+
+        nums = [1, 2, 3]
+        it = iterator_from(nums).rev()
+        nums.pop()
+        with pytest.raises(RuntimeError, match="list changed size"):
+            next(it)
+        with pytest.raises(StopIteration):
+            next(it)
+
+    def test_list_snapshot(self) -> None:
+        nums = [1, 2, 3]
+        it = iterator_from(nums, snapshot=True)
+        nums.append(4)
+        nums[0] = 0
+        assert len(it) == 3
+        assert it.to_list() == [1, 2, 3]
+
+    def test_list_item_assignment_is_seen(self) -> None:
+        nums = [1, 2, 3]
+        it = iterator_from(nums)
+        nums[2] = 30
+        assert it.to_list() == [1, 2, 30]
+
+    def test_container_snapshot(self) -> None:
+        d = {"a": 1, "b": 2}
+        it = iterator_from(d, snapshot=True).map(lambda k: d.setdefault(k * 2, 0))
+        assert it.to_list() == [0, 0]
+
+        s = {1, 2}
+        it = iterator_from(s, snapshot=True)
+        assert isinstance(it, ExactSizeIterator)
+        s.clear()
+        assert it.to_set() == {1, 2}
+
+    def test_size_hint(self) -> None:
+        it = iterator_from([1, 2, 3])
+        assert isinstance(it, SizedDoubleEndedIterator)
+        assert it.size_hint() == (3, 3)
+        next(it)
+        assert it.size_hint() == (2, 2)
+        assert it.interleave(x for x in [4]).size_hint() == (2, None)
+
+    def test_size_hint_of_wrapped_iterators(self) -> None:
+        assert iterator_from(x for x in [1, 2]).size_hint() == (0, None)
+        a, b = iterator_from(iter([1, 2, 3])).tee(2)
+        assert next(a) == 1
+        assert a.size_hint() == (2, None)
+        assert b.size_hint() == (3, None)
+
+    def test_list_preallocates(self) -> None:
+        it = iterator_from([1, 2, 3]).map(lambda x: x * 2)
+        assert it.__length_hint__() == 3
+        assert list(it) == [2, 4, 6]
+        assert it.__length_hint__() == 0
@@ -0,0 +1,149 @@
+import os
+import pickle
+import subprocess
+import sys
+from typing import Any
+
+import pytest
+from py_combinator import (
+    DoubleEndedIterator,
+    InfiniteIterator,
+    SizedDoubleEndedIterator,
+    count,
+    from_state,
+    iterator_from,
+    repeat,
+)
+from py_combinator import range as lib_range
+
+# ruff: noqa: E731 S101
+
+
+def double(x: int) -> int:
+    return x * 2
+
+
+def is_odd(x: int) -> bool:
+    return x % 2 == 1
+
+
+def to_int(x: str) -> int:
+    return int(x)
+
+
+def fail(x: Any) -> Any:
+    raise ValueError(x)
+
+
+class TestState:
+    def test_resume_list_pipeline(self) -> None:
+        it = iterator_from([1, 2, 3, 4, 5]).filter(is_odd).map(double)
+        assert next(it) == 2
+        resumed = from_state(it.state())
+        assert isinstance(resumed, DoubleEndedIterator)
+        assert resumed.to_list() == [6, 10]
+        assert it.to_list() == [6, 10]
+
+    def test_pickle_mid_iteration(self) -> None:
+        it = lib_range(10).map(double).enumerate()
+        for _ in range(3):
+            next(it)
+        resumed = pickle.loads(pickle.dumps(it))
+        assert isinstance(resumed, SizedDoubleEndedIterator)
+        assert len(resumed) == 7
+        assert [next(resumed), next(resumed)] == [(3, 6), (4, 8)]
+        assert resumed.rev().take(1).to_list() == [(9, 18)]
+
+    def test_pickle_sources(self) -> None:
+        sources = [
+            iterator_from((1, 2, 3)),
+            iterator_from("abc"),
+            iterator_from(b"abc"),
+            iterator_from(range(2**70, 2**70 + 9, 4)),
+            repeat("a", 3),
+            iterator_from([3, 1, 2]).rev(),
+        ]
+        for it in sources:
+            next(it)
+            assert pickle.loads(pickle.dumps(it)).to_list() == it.to_list()
+
+    def test_pickle_infinite(self) -> None:
+        it = count(5, 2).map(double)
+        next(it)
+        resumed = pickle.loads(pickle.dumps(it))
+        assert isinstance(resumed, InfiniteIterator)
+        assert resumed.take(2).to_list() == [14, 18]
+        assert pickle.loads(pickle.dumps(repeat(0))).take(2).to_list() == [0, 0]
+
+    def test_pickle_taken(self) -> None:
+        it = iterator_from([1, 2, 3, 4]).filter(is_odd).take(2)
+        assert next(it) == 1
+        resumed = pickle.loads(pickle.dumps(it))
+        assert isinstance(resumed, DoubleEndedIterator)
+        assert resumed.to_list() == [3]
+        it = iterator_from([1, 2, 3, 4, 5]).filter(is_odd).take(2).rev()
+        assert next(it) == 3
+        assert pickle.loads(pickle.dumps(it)).to_list() == [1]
+        assert pickle.loads(pickle.dumps(lib_range(5).take(3).rev())).to_list() == [2, 1, 0]
+
+    def test_pickle_skipped(self) -> None:
+        it = iterator_from([1, 2, 3, 4, 5]).filter(is_odd).skip(1)
+        assert next(it) == 3
+        assert pickle.loads(pickle.dumps(it)).to_list() == [5]
+        # the elements still to be skipped are skipped after resuming
+        resumed = pickle.loads(pickle.dumps(lib_range(6).skip(2)))
+        assert isinstance(resumed, SizedDoubleEndedIterator)
+        assert resumed.to_list() == [2, 3, 4, 5]
+
+    def test_pickle_keeps_notes(self) -> None:
+        it = iterator_from([1, 2]).map(double).map(fail)
+        with pytest.raises(ValueError, match="2"):
+            next(it)
+        resumed = pickle.loads(pickle.dumps(it))
+        with pytest.raises(ValueError, match="4") as e:
+            next(resumed)
+        assert e.value.__notes__ == ["in map #2, on element 1: 4"]
+
+    def test_pickle_catch(self) -> None:
+        it = iterator_from(["1", "x", "3"]).map(to_int).catch(ValueError)
+        assert pickle.loads(pickle.dumps(it)).to_list() == [1, 3]
+
+    def test_resume_in_another_process(self) -> None:
+        it = iterator_from([1, 2, 3, 4]).enumerate().rev()
+        next(it)
+        result = subprocess.run(
+            [sys.executable, "-c", "import pickle, sys; print(pickle.load(sys.stdin.buffer).to_list())"],
+            input=pickle.dumps(it),
+            capture_output=True,
+            check=True,
+            env={**os.environ, "PYTHONPATH": os.pathsep.join(sys.path)},
+        )
+        assert result.stdout.decode().strip() == "[(2, 3), (1, 2), (0, 1)]"
+
+    @pytest.mark.parametrize(
+        ("build", "stage"),
+        [
+            (lambda: iterator_from(x for x in [1]).map(double), "a Python iterator"),
+            (lambda: iterator_from([1]).par_map(double), "par_map"),
+            (lambda: iterator_from({1, 2}), "a dict or set"),
+            (lambda: iterator_from([1]).intersperse(0), "intersperse"),
+            (lambda: iterator_from([1, 2]).tee(2)[0], "tee"),
+        ],
+    )
+    def test_unsaveable(self, build: Any, stage: str) -> None:
+        it = build()
+        with pytest.raises(TypeError, match=f"cannot save the state of an iterator built with {stage}"):
+            it.state()
+        with pytest.raises(TypeError, match=stage):
+            pickle.dumps(it)
+
+    def test_invalid_state(self) -> None:
+        for state in [None, ("base",), ("base", ("unknown",)), ("infinite", ("empty",))]:
+            with pytest.raises(ValueError, match="invalid iterator state"):
+                from_state(state)
+        with pytest.raises(ValueError, match="invalid iterator state"):
+            from_state(("base", ("sequence", [1, 2], 2, 1, 3)))
+        # positions within a sequence of another length, such as bytes that got shorter
+        for sequence in [[1, 2], b"ab"]:
+            with pytest.raises(ValueError, match="invalid iterator state"):
+                from_state(("sized", ("sequence", sequence, 3, 0, 3)))
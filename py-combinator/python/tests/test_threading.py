@@ -0,0 +1,93 @@
+import sys
+import sysconfig
+from collections import Counter
+from collections.abc import Callable, Iterator
+from concurrent.futures import ThreadPoolExecutor
+from threading import Barrier
+from typing import Any
+
+import pytest
+from py_combinator import iterator_from
+
+# ruff: noqa: E731 S101
+
+THREADS = 8
+
+
+@pytest.fixture(autouse=True)
+def frequent_switches() -> Iterator[None]:
+    """Makes threads of GIL builds switch often enough to interleave `__next__` calls."""
+    interval = sys.getswitchinterval()
+    sys.setswitchinterval(1e-6)
+    yield
+    sys.setswitchinterval(interval)
+
+
+def drain_concurrently(it: Any) -> list[Any]:
+    barrier = Barrier(THREADS)
+
+    def drain() -> list[Any]:
+        barrier.wait()
+        return list(it)
+
+    with ThreadPoolExecutor(THREADS) as pool:
+        futures = [pool.submit(drain) for _ in range(THREADS)]
+        return [x for future in futures for x in future.result()]
+
+
+class TestThreading:
+    @pytest.mark.skipif(
+        not sysconfig.get_config_var("Py_GIL_DISABLED"),
+        reason="requires a free-threaded build",
+    )
+    def test_gil_stays_disabled(self) -> None:
+        # importing an extension which needs the GIL enables it again
+        assert not sys._is_gil_enabled()  # noqa: SLF001
+
+    @pytest.mark.parametrize(
+        "creator",
+        [
+            lambda data: iterator_from(x for x in data),
+            lambda data: iterator_from(data),
+        ],
+    )
+    def test_concurrent_next(self, creator: Callable[[list[int]], Any]) -> None:
+        nums = list(range(20_000))
+        it = creator(nums).map(lambda x: x + 1).filter(lambda x: x % 2 == 0)
+        expected = [x + 1 for x in nums if (x + 1) % 2 == 0]
+        assert Counter(drain_concurrently(it)) == Counter(expected)
+
+    def test_concurrent_next_with_par_map(self) -> None:
+        nums = list(range(5_000))
+        it = iterator_from(nums).par_map(lambda x: x * 2, workers=4, chunk_size=8)
+        assert sorted(drain_concurrently(it)) == [x * 2 for x in nums]
+
+    def test_reentrant_next_raises(self) -> None:
+        it = iterator_from([1, 2])
+        it = it.map(lambda _: next(it))
+        with pytest.raises(ValueError, match="already executing"):
+            next(it)
+
+    def test_reentrant_method_raises(self) -> None:
+        it = iterator_from([1, 2])
+        it = it.map(lambda _: it.to_list())
+        with pytest.raises(ValueError, match="already executing"):
+            next(it)
+
+    def test_method_waits_for_concurrent_next(self) -> None:
+        nums = list(range(20_000))
+        it = iterator_from(x for x in nums).map(lambda x: x + 1)
+        barrier = Barrier(2)
+
+        def drain() -> list[int]:
+            barrier.wait()
+            return list(it)
+
+        def collect() -> list[int]:
+            barrier.wait()
+            return it.to_list()
+
+        with ThreadPoolExecutor(2) as pool:
+            futures = [pool.submit(drain), pool.submit(collect)]
+            results = [x for future in futures for x in future.result()]
+        assert Counter(results) == Counter(x + 1 for x in nums)
@@ -0,0 +1,271 @@
+use std::collections::VecDeque;
+
+use pyo3::{
+    PyTraverseError, PyVisit,
+    exceptions::{PyRuntimeError, PyStopAsyncIteration, PyValueError},
+    intern,
+    prelude::*,
+    types::{IntoPyDict, PyFunction},
+};
+
+use crate::async_iterator::{
+    self, AsyncPipeline, Coroutine, Input, Poll, PyAsyncIterator, PyAwaitable,
+};
+
+/// The result of a callback, either scheduled as a task or available straight away
+enum InFlight {
+    Task(Py<PyAny>),
+    Ready(Py<PyAny>),
+}
+
+/// An async iterator mapping the elements of another, with up to `concurrency` of the awaitables
+/// its callback returns scheduled on the running event loop at once
+#[pyclass]
+pub struct PyAmap {
+    upstream: Py<PyAsyncIterator>,
+    f: Py<PyFunction>,
+    concurrency: usize,
+    ordered: bool,
+    // in the order their elements were pulled
+    in_flight: VecDeque<InFlight>,
+    exhausted: bool,
+}
+
+impl PyAmap {
+    pub fn pipeline(
+        upstream: AsyncPipeline,
+        f: Py<PyFunction>,
+        concurrency: usize,
+        ordered: bool,
+    ) -> PyResult<AsyncPipeline> {
+        if concurrency == 0 {
+            return Err(PyValueError::new_err("concurrency must be at least 1"));
+        }
+
+        Python::with_gil(|py| {
+            let amap = PyAmap {
+                upstream: Py::new(py, PyAsyncIterator::new(Box::new(upstream)))?,
+                f,
+                concurrency,
+                ordered,
+                in_flight: VecDeque::new(),
+                exhausted: false,
+            };
+            Ok(AsyncPipeline::new(Py::new(py, amap)?.into_any()))
+        })
+    }
+
+    /// Returns the position of a result that can be yielded without waiting, which is the first
+    /// one when ordered unless a later callback has already raised
+    fn finished(&self, py: Python<'_>) -> PyResult<Option<usize>> {
+        for (i, x) in self.in_flight.iter().enumerate() {
+            let InFlight::Task(task) = x else {
+                if i == 0 || !self.ordered {
+                    return Ok(Some(i));
+                }
+                continue;
+            };
+            let task = task.bind(py);
+            if !task.call_method0(intern!(py, "done"))?.is_truthy()? {
+                continue;
+            }
+            if i == 0 || !self.ordered || !task.call_method0(intern!(py, "exception"))?.is_none() {
+                return Ok(Some(i));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Schedules `f(x)` if it returns an awaitable
+    fn schedule(&mut self, py: Python<'_>, x: Bound<'_, PyAny>) -> PyResult<()> {
+        let result = self.f.bind(py).call1((x,))?;
+        let x = if async_iterator::is_awaitable(&result)? {
+            InFlight::Task(
+                py.import(intern!(py, "asyncio"))?
+                    .call_method1(intern!(py, "ensure_future"), (result,))?
+                    .unbind(),
+            )
+        } else {
+            InFlight::Ready(result.unbind())
+        };
+        self.in_flight.push_back(x);
+        Ok(())
+    }
+
+    /// Returns an awaitable finishing once any of the callbacks in flight does
+    fn wait<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let asyncio = py.import(intern!(py, "asyncio"))?;
+        let tasks = self
+            .in_flight
+            .iter()
+            .filter_map(|x| match x {
+                InFlight::Task(task) => Some(task.clone_ref(py)),
+                InFlight::Ready(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let kwargs = [(
+            intern!(py, "return_when"),
+            asyncio.getattr(intern!(py, "FIRST_COMPLETED"))?,
+        )]
+        .into_py_dict(py)?;
+        asyncio.call_method(intern!(py, "wait"), (tasks,), Some(&kwargs))
+    }
+
+    /// Cancels every task still in flight, ending the iterator
+    fn abort(&mut self, py: Python<'_>) {
+        self.exhausted = true;
+        for x in self.in_flight.drain(..) {
+            if let InFlight::Task(task) = x {
+                // cancelling fails once the event loop is closed, when there's nothing to stop
+                let _ = task.call_method0(py, intern!(py, "cancel"));
+            }
+        }
+    }
+}
+
+impl Drop for PyAmap {
+    fn drop(&mut self) {
+        Python::with_gil(|py| self.abort(py));
+    }
+}
+
+#[pymethods]
+impl PyAmap {
+    pub fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    pub fn __anext__(slf: Py<Self>, py: Python<'_>) -> PyResult<Py<PyAwaitable>> {
+        Py::new(
+            py,
+            PyAwaitable::new(AmapNext {
+                amap: slf,
+                state: State::Start,
+            }),
+        )
+    }
+
+    // cycles through an amap always pass through the async iterator wrapping it, which breaks them
+    #[allow(clippy::needless_pass_by_value)] // required by pyo3
+    pub fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.upstream)?;
+        visit.call(&self.f)?;
+        for x in &self.in_flight {
+            match x {
+                InFlight::Task(x) | InFlight::Ready(x) => visit.call(x)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+enum State {
+    // about to yield a finished result, or start waiting for one
+    Start,
+    Pulling(Py<PyAny>),
+    Waiting(Py<PyAny>),
+    Finished,
+}
+
+/// Pulls elements and schedules their callbacks until one of their results can be yielded
+struct AmapNext {
+    amap: Py<PyAmap>,
+    state: State,
+}
+
+impl AmapNext {
+    fn drive<'py>(&mut self, py: Python<'py>, mut input: Input<'py>) -> PyResult<Poll<'py>> {
+        loop {
+            match std::mem::replace(&mut self.state, State::Finished) {
+                State::Finished => {
+                    return Err(PyRuntimeError::new_err(
+                        "cannot reuse already awaited awaitable",
+                    ));
+                }
+                State::Start => {
+                    if let Input::Throw(e) = input {
+                        return Err(e);
+                    }
+
+                    let mut amap = self.amap.borrow_mut(py);
+                    if let Some(i) = amap.finished(py)? {
+                        return match amap.in_flight.remove(i) {
+                            Some(InFlight::Task(task)) => task
+                                .bind(py)
+                                .call_method0(intern!(py, "result"))
+                                .map(Poll::Ready),
+                            Some(InFlight::Ready(x)) => Ok(Poll::Ready(x.into_bound(py))),
+                            None => unreachable!(),
+                        };
+                    }
+
+                    let awaiting = if !amap.exhausted && amap.in_flight.len() < amap.concurrency {
+                        // the upstream iterator's callbacks run without this one borrowed
+                        let upstream = amap.upstream.clone_ref(py);
+                        drop(amap);
+                        State::Pulling(async_iterator::anext(upstream.bind(py))?.unbind())
+                    } else if amap.in_flight.is_empty() {
+                        return Err(PyStopAsyncIteration::new_err(()));
+                    } else {
+                        let wait = amap.wait(py)?;
+                        State::Waiting(wait.call_method0(intern!(py, "__await__"))?.unbind())
+                    };
+                    self.state = awaiting;
+                }
+                State::Pulling(awaiting) => {
+                    let input =
+                        std::mem::replace(&mut input, Input::Send(py.None().into_bound(py)));
+                    match async_iterator::resume(awaiting.bind(py), input) {
+                        Ok(Poll::Yield(y)) => {
+                            self.state = State::Pulling(awaiting);
+                            return Ok(Poll::Yield(y));
+                        }
+                        Ok(Poll::Ready(x)) => self.amap.borrow_mut(py).schedule(py, x)?,
+                        Err(e) if e.is_instance_of::<PyStopAsyncIteration>(py) => {
+                            self.amap.borrow_mut(py).exhausted = true;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                    self.state = State::Start;
+                }
+                State::Waiting(awaiting) => {
+                    let input =
+                        std::mem::replace(&mut input, Input::Send(py.None().into_bound(py)));
+                    if let Poll::Yield(y) = async_iterator::resume(awaiting.bind(py), input)? {
+                        self.state = State::Waiting(awaiting);
+                        return Ok(Poll::Yield(y));
+                    }
+                    self.state = State::Start;
+                }
+            }
+        }
+    }
+}
+
+impl Coroutine for AmapNext {
+    fn step<'py>(&mut self, py: Python<'py>, input: Input<'py>) -> PyResult<Bound<'py, PyAny>> {
+        match self.drive(py, input) {
+            Ok(Poll::Yield(y)) => Ok(y),
+            Ok(Poll::Ready(x)) => {
+                self.state = State::Finished;
+                Err(async_iterator::ready(x))
+            }
+            Err(e) => {
+                self.state = State::Finished;
+                if !e.is_instance_of::<PyStopAsyncIteration>(py) {
+                    self.amap.borrow_mut(py).abort(py);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        if let State::Pulling(awaiting) | State::Waiting(awaiting) =
+            std::mem::replace(&mut self.state, State::Finished)
+        {
+            async_iterator::close(awaiting.bind(py))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,375 @@
+use pyo3::{
+    IntoPyObjectExt, PyTraverseError, PyVisit,
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::PyRange,
+};
+
+use crate::{
+    base_iterator::{PyBaseIteratorT, exact_len},
+    container_iterator::PyContainerIterator,
+    double_ended_iterator::PyDoubleEndedIteratorT,
+    exact_size_iterator::PyExactSizeIteratorT,
+    iter_iterator::PyIterIterator,
+    iterators::{
+        PyBaseIterator, PyDoubleEndedIterator, PyExactSizeIterator, PyInfiniteIterator,
+        PySizedDoubleEndedIterator,
+    },
+    py_iter::PyIter,
+    range_iterator::PyRangeIterator,
+    sequence_iterator::PySequenceIterator,
+    sized_double_ended_iterator::PySizedDoubleEndedIteratorT,
+};
+
+/// The iterator class requested from `iterator_from`
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Auto,
+    Base,
+    ExactSize,
+    DoubleEnded,
+    SizedDoubleEnded,
+}
+
+impl Kind {
+    pub fn parse(kind: &str) -> PyResult<Self> {
+        match kind {
+            "auto" => Ok(Kind::Auto),
+            "base" => Ok(Kind::Base),
+            "exact_size" => Ok(Kind::ExactSize),
+            "double_ended" => Ok(Kind::DoubleEnded),
+            "sized_double_ended" => Ok(Kind::SizedDoubleEnded),
+            _ => Err(PyValueError::new_err(format!(
+                "kind must be one of 'auto', 'base', 'exact_size', 'double_ended' or \
+                 'sized_double_ended', got '{kind}'"
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Auto => "auto",
+            Kind::Base => "base",
+            Kind::ExactSize => "exact_size",
+            Kind::DoubleEnded => "double_ended",
+            Kind::SizedDoubleEnded => "sized_double_ended",
+        }
+    }
+
+    /// Checks that an iterator with the given capabilities can be turned into this kind
+    pub fn check(
+        self,
+        exact_size: bool,
+        double_ended: bool,
+        source: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        let (needs_exact_size, needs_double_ended) = match self {
+            Kind::Auto | Kind::Base => (false, false),
+            Kind::ExactSize => (true, false),
+            Kind::DoubleEnded => (false, true),
+            Kind::SizedDoubleEnded => (true, true),
+        };
+
+        if needs_double_ended && !double_ended {
+            Err(PyTypeError::new_err(format!(
+                "cannot build a {} iterator from {}, as it can't be iterated from the back",
+                self.name(),
+                source.get_type().name()?
+            )))
+        } else if needs_exact_size && !exact_size {
+            Err(PyTypeError::new_err(format!(
+                "cannot build a {} iterator from {} without a length_hint",
+                self.name(),
+                source.get_type().name()?
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Trusts a caller-supplied length, yielding at most `remaining` elements and raising if the
+/// iterator runs out before that
+pub struct WithLength<I> {
+    iter: I,
+    remaining: usize,
+}
+
+impl<I> WithLength<I> {
+    pub fn new(iter: I, remaining: usize) -> Self {
+        WithLength { iter, remaining }
+    }
+}
+
+impl<I> Iterator for WithLength<I>
+where
+    I: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let remaining = std::mem::take(&mut self.remaining);
+        match self.iter.next() {
+            Some(x) => {
+                self.remaining = remaining - 1;
+                Some(x)
+            }
+            None => Some(Err(PyValueError::new_err(format!(
+                "iterator ended {remaining} element(s) short of its length_hint"
+            )))),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I> DoubleEndedIterator for WithLength<I>
+where
+    I: DoubleEndedIterator<Item = PyResult<Py<PyAny>>>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let remaining = std::mem::take(&mut self.remaining);
+        match self.iter.next_back() {
+            Some(x) => {
+                self.remaining = remaining - 1;
+                Some(x)
+            }
+            None => Some(Err(PyValueError::new_err(format!(
+                "iterator ended {remaining} element(s) short of its length_hint"
+            )))),
+        }
+    }
+}
+
+impl<I> ExactSizeIterator for WithLength<I> where I: Iterator<Item = PyResult<Py<PyAny>>> {}
+
+impl<I> PyIter for WithLength<I>
+where
+    I: PyIter,
+{
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.iter.traverse(visit)
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.iter.close(py)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        self.iter.stage_count(name)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(WithLength {
+            iter: self.iter.try_clone(py)?,
+            remaining: self.remaining,
+        })
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        ("with_length", self.iter.state(py)?, self.remaining).into_bound_py_any(py)
+    }
+}
+
+/// The inner iterator of any of the iterator classes, tagged with its capabilities
+pub enum AnyIterator {
+    Base(PyBaseIteratorT),
+    DoubleEnded(PyDoubleEndedIteratorT),
+    ExactSize(PyExactSizeIteratorT),
+    SizedDoubleEnded(PySizedDoubleEndedIteratorT),
+    Infinite(PyBaseIteratorT),
+}
+
+impl AnyIterator {
+    /// Builds the most capable iterator over `iterable`. Iterator classes are consumed, handing
+    /// over their inner iterator as is.
+    pub fn from_iterable(iterable: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::from_iterable_with(iterable, false)
+    }
+
+    /// Like `from_iterable`, copying mutable containers up front if `snapshot` is set rather
+    /// than raising once they change size
+    pub fn from_iterable_with(iterable: &Bound<'_, PyAny>, snapshot: bool) -> PyResult<Self> {
+        if let Some(range) = iterable
+            .downcast::<PyRange>()
+            .ok()
+            .map(PyRangeIterator::new)
+            .transpose()?
+            .flatten()
+        {
+            Ok(AnyIterator::SizedDoubleEnded(Box::new(range)))
+        } else if PyContainerIterator::is_dict_like(iterable) {
+            if snapshot {
+                Ok(AnyIterator::SizedDoubleEnded(Box::new(
+                    PySequenceIterator::new(iterable, true)?,
+                )))
+            } else {
+                Ok(AnyIterator::SizedDoubleEnded(Box::new(
+                    PyContainerIterator::new(iterable)?,
+                )))
+            }
+        } else if PyContainerIterator::is_set_like(iterable) {
+            if snapshot {
+                Ok(AnyIterator::ExactSize(Box::new(PySequenceIterator::new(
+                    iterable, true,
+                )?)))
+            } else {
+                Ok(AnyIterator::ExactSize(Box::new(PyContainerIterator::new(
+                    iterable,
+                )?)))
+            }
+        } else if PySequenceIterator::accepts(iterable)? {
+            Ok(AnyIterator::SizedDoubleEnded(Box::new(
+                PySequenceIterator::new(iterable, snapshot)?,
+            )))
+        } else if let Ok(it) = iterable.downcast::<PyBaseIterator>() {
+            Ok(AnyIterator::Base(it.get().take_inner()?))
+        } else if let Ok(it) = iterable.downcast::<PyDoubleEndedIterator>() {
+            Ok(AnyIterator::DoubleEnded(it.get().take_inner()?))
+        } else if let Ok(it) = iterable.downcast::<PyExactSizeIterator>() {
+            Ok(AnyIterator::ExactSize(it.get().take_inner()?))
+        } else if let Ok(it) = iterable.downcast::<PySizedDoubleEndedIterator>() {
+            Ok(AnyIterator::SizedDoubleEnded(it.get().take_inner()?))
+        } else if let Ok(it) = iterable.downcast::<PyInfiniteIterator>() {
+            Ok(AnyIterator::Infinite(it.get().take_inner()?))
+        } else {
+            match iterable.try_iter() {
+                Ok(it) => Ok(AnyIterator::Base(Box::new(PyIterIterator::new(&it)))),
+                Err(e) => Err(PyTypeError::new_err(format!(
+                    "Cannot construct iterator from type {} ({})",
+                    iterable.get_type().name()?,
+                    e,
+                ))),
+            }
+        }
+    }
+
+    /// The capabilities of `iterable` if it's one of the iterator classes, which are known
+    /// without consuming it
+    pub fn class_capabilities(iterable: &Bound<'_, PyAny>) -> Option<(bool, bool)> {
+        if iterable.is_instance_of::<PyBaseIterator>()
+            || iterable.is_instance_of::<PyInfiniteIterator>()
+        {
+            Some((false, false))
+        } else if iterable.is_instance_of::<PyDoubleEndedIterator>() {
+            Some((false, true))
+        } else if iterable.is_instance_of::<PyExactSizeIterator>() {
+            Some((true, false))
+        } else if iterable.is_instance_of::<PySizedDoubleEndedIterator>() {
+            Some((true, true))
+        } else {
+            None
+        }
+    }
+
+    pub fn capabilities(&self) -> (bool, bool) {
+        match self {
+            AnyIterator::Base(_) | AnyIterator::Infinite(_) => (false, false),
+            AnyIterator::DoubleEnded(_) => (false, true),
+            AnyIterator::ExactSize(_) => (true, false),
+            AnyIterator::SizedDoubleEnded(_) => (true, true),
+        }
+    }
+
+    /// Gives the iterator a caller-supplied length, checking it against the actual one if it's
+    /// already known
+    pub fn with_length(self, length: usize) -> PyResult<Self> {
+        let known = match &self {
+            AnyIterator::ExactSize(it) => Some(exact_len(it.size_hint())?),
+            AnyIterator::SizedDoubleEnded(it) => Some(exact_len(it.size_hint())?),
+            _ => None,
+        };
+
+        match (self, known) {
+            (it, Some(len)) if len == length => Ok(it),
+            (_, Some(len)) => Err(PyValueError::new_err(format!(
+                "length_hint is {length}, but the iterable has {len} element(s)"
+            ))),
+            (it, None) => Ok(it.trust_length(length)),
+        }
+    }
+
+    /// Gives the iterator a caller-supplied length without checking it
+    pub fn trust_length(self, length: usize) -> Self {
+        match self {
+            AnyIterator::DoubleEnded(iter) => AnyIterator::SizedDoubleEnded(Box::new(WithLength {
+                iter,
+                remaining: length,
+            })),
+            AnyIterator::SizedDoubleEnded(iter) => {
+                AnyIterator::SizedDoubleEnded(Box::new(WithLength {
+                    iter,
+                    remaining: length,
+                }))
+            }
+            it => AnyIterator::ExactSize(Box::new(WithLength {
+                iter: it.into_base(),
+                remaining: length,
+            })),
+        }
+    }
+
+    /// Converts to the given kind, dropping any capabilities it doesn't ask for
+    pub fn into_kind(self, kind: Kind, source: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let (exact_size, double_ended) = self.capabilities();
+        kind.check(exact_size, double_ended, source)?;
+
+        Ok(match (kind, self) {
+            (Kind::Base, it) => AnyIterator::Base(it.into_base()),
+            (Kind::ExactSize, AnyIterator::SizedDoubleEnded(it)) => AnyIterator::ExactSize(it),
+            (Kind::DoubleEnded, AnyIterator::SizedDoubleEnded(it)) => AnyIterator::DoubleEnded(it),
+            (_, it) => it,
+        })
+    }
+
+    pub fn into_base(self) -> PyBaseIteratorT {
+        match self {
+            AnyIterator::Base(it) | AnyIterator::Infinite(it) => it,
+            AnyIterator::DoubleEnded(it) => it,
+            AnyIterator::ExactSize(it) => it,
+            AnyIterator::SizedDoubleEnded(it) => it,
+        }
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        matches!(self, AnyIterator::Infinite(_))
+    }
+
+    pub fn is_exact_size(&self) -> bool {
+        matches!(
+            self,
+            AnyIterator::ExactSize(_) | AnyIterator::SizedDoubleEnded(_)
+        )
+    }
+
+    /// Returns the inner iterator if its length is known
+    pub fn into_exact_size(self) -> Result<PyExactSizeIteratorT, Self> {
+        match self {
+            AnyIterator::ExactSize(it) => Ok(it),
+            AnyIterator::SizedDoubleEnded(it) => Ok(it),
+            other => Err(other),
+        }
+    }
+
+    pub fn into_py_any(self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        match self {
+            AnyIterator::Base(it) => PyBaseIterator::new(it).into_py_any(py),
+            AnyIterator::DoubleEnded(it) => PyDoubleEndedIterator::new(it).into_py_any(py),
+            AnyIterator::ExactSize(it) => PyExactSizeIterator::new(it).into_py_any(py),
+            AnyIterator::SizedDoubleEnded(it) => {
+                PySizedDoubleEndedIterator::new(it).into_py_any(py)
+            }
+            AnyIterator::Infinite(it) => PyInfiniteIterator::new(it).into_py_any(py),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::convert::Infallible;
+
+use pyo3::{exceptions::PyTypeError, exceptions::PyValueError, prelude::*};
+
+/// What `to_dict` does with a key it has already seen
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OnDuplicate {
+    Last,
+    First,
+    Error,
+}
+
+impl FromPyObject<'_> for OnDuplicate {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        match &*ob.extract::<std::borrow::Cow<'_, str>>()? {
+            "last" => Ok(OnDuplicate::Last),
+            "first" => Ok(OnDuplicate::First),
+            "error" => Ok(OnDuplicate::Error),
+            other => Err(PyValueError::new_err(format!(
+                "on_duplicate must be one of 'last', 'first' or 'error', got '{other}'"
+            ))),
+        }
+    }
+}
+
+/// An object that can be iterated. Arguments are checked when they're extracted, so that a bad
+/// one raises before the iterator it's passed along with is emptied.
+pub struct Iterable(pub Py<PyAny>);
+
+impl FromPyObject<'_> for Iterable {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        ob.try_iter()?;
+        Ok(Iterable(ob.clone().unbind()))
+    }
+}
+
+impl<'py> IntoPyObject<'py> for Iterable {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = Infallible;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(self.0.into_bound(py))
+    }
+}
+
+/// An object that can be called, checked the same way as [`Iterable`]
+pub struct Callable(pub Py<PyAny>);
+
+impl FromPyObject<'_> for Callable {
+    fn extract_bound(ob: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if ob.is_callable() {
+            Ok(Callable(ob.clone().unbind()))
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "'{}' object is not callable",
+                ob.get_type().name()?
+            )))
+        }
+    }
+}
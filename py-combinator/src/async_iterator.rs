@@ -0,0 +1,633 @@
+use pyo3::{
+    IntoPyObjectExt, PyTraverseError, PyVisit,
+    exceptions::{
+        PyBaseException, PyRuntimeError, PyStopAsyncIteration, PyStopIteration, PyTypeError,
+    },
+    intern,
+    prelude::*,
+    types::{PyFunction, PyList},
+};
+
+use crate::exclusive::Exclusive;
+
+/// A per-element stage of an async iterator. Callbacks may return awaitables, which are awaited
+/// before the element moves on.
+pub enum AsyncStage {
+    Map(Py<PyFunction>),
+    Filter(Py<PyFunction>),
+    // the index of the next element
+    Enumerate(usize),
+    // the number of elements still let through
+    Take(usize),
+}
+
+/// An async iterator followed by the stages applied to each of its elements
+pub struct AsyncPipeline {
+    // `None` once taken out of its iterator class
+    source: Option<Py<PyAny>>,
+    stages: Vec<AsyncStage>,
+}
+
+impl AsyncPipeline {
+    pub fn new(source: Py<PyAny>) -> Self {
+        Self {
+            source: Some(source),
+            stages: Vec::new(),
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            source: None,
+            stages: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn push(mut self, stage: AsyncStage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.source)?;
+        for stage in &self.stages {
+            if let AsyncStage::Map(f) | AsyncStage::Filter(f) = stage {
+                visit.call(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<PyAsyncIteratorT> for AsyncPipeline {
+    fn from(iter: PyAsyncIteratorT) -> Self {
+        *iter
+    }
+}
+
+pub type PyAsyncIteratorT = Box<AsyncPipeline>;
+#[pyclass(frozen, generic)]
+pub struct PyAsyncIterator {
+    iter: Exclusive<PyAsyncIteratorT>,
+}
+
+impl PyAsyncIterator {
+    pub fn new(iter: PyAsyncIteratorT) -> Self {
+        Self {
+            iter: Exclusive::new(iter),
+        }
+    }
+}
+
+#[macros::register_methods(self_generic = S)]
+impl crate::async_iterator::PyAsyncIterator {
+    #[allow(clippy::unnecessary_box_returns)] // generated methods box every iterator
+    #[macros::method_self_arg]
+    pub fn take_inner(&self) -> PyResult<PyAsyncIteratorT> {
+        Python::with_gil(|py| {
+            Ok(std::mem::replace(
+                &mut *self.iter.lock(py)?,
+                Box::new(AsyncPipeline::empty()),
+            ))
+        })
+    }
+
+    #[doc = "Awaits every element and collects them into a list"]
+    #[macros::return_literal]
+    pub fn to_list<S>(iter: S) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
+    where
+        S: Into<AsyncPipeline>,
+    {
+        Driver::consuming(iter.into(), Goal::ToList(Vec::new()))
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // for f
+    #[macros::return_literal]
+    #[doc = "Folds every element into an accumulator by repeatedly applying `f`,
+             awaiting its result if it's awaitable.
+             
+             Examples:
+                 iter # [2, 4, 6]
+                 await iter.fold(1, lambda a, x: a * x) # 48"]
+    pub fn fold<S>(
+        iter: S,
+        init: pyo3::Py<pyo3::types::PyAny>,
+        f: pyo3::Py<pyo3::types::PyFunction>,
+    ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
+    where
+        S: Into<AsyncPipeline>,
+    {
+        Driver::consuming(iter.into(), Goal::Fold { acc: init, f })
+    }
+
+    #[doc = "Creates a new async iterator by applying `f` to each element, awaiting
+             its result if it's awaitable.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.map(lambda x: x + 1) # [2, 3, 4]"]
+    pub fn map<S>(iter: S, f: pyo3::Py<pyo3::types::PyFunction>) -> AsyncPipeline
+    where
+        S: Into<AsyncPipeline>,
+    {
+        iter.into().push(AsyncStage::Map(f))
+    }
+
+    #[doc = "Creates a new async iterator that yields elements for which `f` returns
+             `true`, awaiting its result if it's awaitable.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.filter(lambda x: x % 2 == 0) # [2]"]
+    pub fn filter<S>(iter: S, f: pyo3::Py<pyo3::types::PyFunction>) -> AsyncPipeline
+    where
+        S: Into<AsyncPipeline>,
+    {
+        iter.into().push(AsyncStage::Filter(f))
+    }
+
+    #[macros::fallible]
+    #[macros::signature(f, concurrency, ordered = true)]
+    #[doc = "Creates a new async iterator by applying `f` to each element, with up
+             to `concurrency` of the awaitables it returns scheduled as tasks on
+             the running event loop at once. Results are yielded in order, or as
+             soon as they're ready with `ordered=False`. Tasks still in flight
+             are cancelled once one of them raises or the iterator is dropped.
+             
+             Examples:
+                 iter # ['a', 'b', 'c']
+                 iter.amap(fetch, concurrency=2) # [await fetch('a'), ...]"]
+    pub fn amap<S>(
+        iter: S,
+        f: pyo3::Py<pyo3::types::PyFunction>,
+        concurrency: usize,
+        ordered: bool,
+    ) -> pyo3::PyResult<AsyncPipeline>
+    where
+        S: Into<AsyncPipeline>,
+    {
+        crate::amap::PyAmap::pipeline(iter.into(), f, concurrency, ordered)
+    }
+
+    #[doc = "Creates a new async iterator which gives the current iteration count
+             as well as the value.
+             
+             Examples:
+                 iter # [4, 9, 16]
+                 iter.enumerate() # [(0, 4), (1, 9), (2, 16)]"]
+    pub fn enumerate<S>(iter: S) -> AsyncPipeline
+    where
+        S: Into<AsyncPipeline>,
+    {
+        iter.into().push(AsyncStage::Enumerate(0))
+    }
+
+    #[doc = "Creates a new async iterator that yields the first `n` elements, or
+             fewer if the iterator runs out sooner.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.take(2) # [1, 2]"]
+    pub fn take<S>(iter: S, n: usize) -> AsyncPipeline
+    where
+        S: Into<AsyncPipeline>,
+    {
+        iter.into().push(AsyncStage::Take(n))
+    }
+}
+
+#[macros::add_trait_methods(PyAsyncIterator)]
+#[pymethods]
+impl PyAsyncIterator {
+    pub fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    pub fn __anext__(slf: Py<Self>, py: Python<'_>) -> PyResult<Py<PyAwaitable>> {
+        Py::new(py, PyAwaitable::new(Driver::new(slf, Goal::Next)))
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // required by pyo3
+    pub fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        // skipped while the iterator is in use, which only keeps its references alive longer
+        if let Some(iter) = self.iter.try_lock() {
+            iter.traverse(&visit)?;
+        }
+        Ok(())
+    }
+
+    pub fn __clear__(&self) {
+        let iter = self
+            .iter
+            .try_lock()
+            .map(|mut iter| std::mem::replace(&mut *iter, Box::new(AsyncPipeline::empty())));
+        drop(iter);
+    }
+}
+
+/// Creates an async iterator over `iterable`, which must implement `__aiter__`
+#[pyfunction]
+pub fn async_iterator_from(iterable: &Bound<'_, PyAny>) -> PyResult<PyAsyncIterator> {
+    let py = iterable.py();
+
+    if let Ok(it) = iterable.downcast::<PyAsyncIterator>() {
+        return Ok(PyAsyncIterator::new(it.get().take_inner()?));
+    }
+
+    if !iterable.get_type().hasattr(intern!(py, "__aiter__"))? {
+        return Err(PyTypeError::new_err(format!(
+            "'{}' object is not an async iterable",
+            iterable.get_type().name()?
+        )));
+    }
+
+    let source = iterable.call_method0(intern!(py, "__aiter__"))?;
+    Ok(PyAsyncIterator::new(Box::new(AsyncPipeline::new(
+        source.unbind(),
+    ))))
+}
+
+/// What an awaitable produces once its async iterator has been driven far enough
+pub enum Goal {
+    Next,
+    ToList(Vec<Py<PyAny>>),
+    Fold { acc: Py<PyAny>, f: Py<PyFunction> },
+}
+
+/// What the awaited result is used for once it's ready
+enum Then {
+    Pulled,
+    Mapped(usize),
+    // the element being filtered
+    Filtered(usize, Py<PyAny>),
+    Folded,
+}
+
+enum State {
+    // about to pull the next element from the source
+    Start,
+    Awaiting { awaiting: Py<PyAny>, then: Then },
+    Finished,
+}
+
+pub enum Input<'py> {
+    Send(Bound<'py, PyAny>),
+    Throw(PyErr),
+}
+
+pub enum Poll<'py> {
+    Yield(Bound<'py, PyAny>),
+    Ready(Bound<'py, PyAny>),
+}
+
+pub fn is_awaitable(x: &Bound<'_, PyAny>) -> PyResult<bool> {
+    x.get_type().hasattr(intern!(x.py(), "__await__"))
+}
+
+/// Returns the iterator driving `x` to completion, if `x` is awaitable
+pub fn awaiting<'py>(x: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyAny>>> {
+    if is_awaitable(x)? {
+        Ok(Some(x.call_method0(intern!(x.py(), "__await__"))?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the iterator driving `source.__anext__()` to completion
+pub fn anext<'py>(source: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let next = source.call_method0(intern!(source.py(), "__anext__"))?;
+    awaiting(&next)?.ok_or_else(|| {
+        PyTypeError::new_err("'async for' received an invalid object from __anext__")
+    })
+}
+
+/// Completes an awaitable with `result`
+pub fn ready(result: Bound<'_, PyAny>) -> PyErr {
+    PyStopIteration::new_err((result.unbind(),))
+}
+
+/// Resumes `awaiting` with `input`, as `yield from` would
+pub fn resume<'py>(awaiting: &Bound<'py, PyAny>, input: Input<'py>) -> PyResult<Poll<'py>> {
+    let py = awaiting.py();
+    let result = match input {
+        Input::Send(value) if value.is_none() => awaiting.call_method0(intern!(py, "__next__")),
+        Input::Send(value) => awaiting.call_method1(intern!(py, "send"), (value,)),
+        Input::Throw(e) => {
+            if !awaiting.hasattr(intern!(py, "throw"))? {
+                return Err(e);
+            }
+            awaiting.call_method1(intern!(py, "throw"), (e.value(py),))
+        }
+    };
+
+    match result {
+        Ok(x) => Ok(Poll::Yield(x)),
+        Err(e) if e.is_instance_of::<PyStopIteration>(py) => {
+            Ok(Poll::Ready(e.value(py).getattr(intern!(py, "value"))?))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Closes `awaiting`, if it can be
+pub fn close(awaiting: &Bound<'_, PyAny>) -> PyResult<()> {
+    let py = awaiting.py();
+    if awaiting.hasattr(intern!(py, "close"))? {
+        awaiting.call_method0(intern!(py, "close"))?;
+    }
+    Ok(())
+}
+
+/// A computation suspended on the awaitables it waits for, run by a `PyAwaitable`
+pub trait Coroutine: Send + Sync {
+    /// Resumes with `input`, returning what the awaitable being waited for yields, or raising
+    /// `StopIteration` with the result once done
+    fn step<'py>(&mut self, py: Python<'py>, input: Input<'py>) -> PyResult<Bound<'py, PyAny>>;
+
+    /// Gives up, closing the awaitable being waited for
+    fn close(&mut self, py: Python<'_>) -> PyResult<()>;
+}
+
+/// Pulls elements of an async iterator through its stages until they reach its goal
+struct Driver {
+    iter: Py<PyAsyncIterator>,
+    goal: Goal,
+    state: State,
+}
+
+impl Driver {
+    fn new(iter: Py<PyAsyncIterator>, goal: Goal) -> Self {
+        Self {
+            iter,
+            goal,
+            state: State::Start,
+        }
+    }
+
+    /// Creates an awaitable over an iterator of its own, for terminals
+    fn consuming(iter: AsyncPipeline, goal: Goal) -> PyResult<Py<PyAny>> {
+        Python::with_gil(|py| {
+            let iter = Py::new(py, PyAsyncIterator::new(Box::new(iter)))?;
+            Ok(Py::new(py, PyAwaitable::new(Self::new(iter, goal)))?.into_any())
+        })
+    }
+
+    /// Starts awaiting the next element of the source, or returns `None` if there's none left
+    fn pull<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let source = {
+            let iter = self.iter.get().iter.lock(py)?;
+            if iter
+                .stages
+                .iter()
+                .any(|stage| matches!(stage, AsyncStage::Take(0)))
+            {
+                return Ok(None);
+            }
+            let Some(source) = &iter.source else {
+                return Ok(None);
+            };
+            source.clone_ref(py)
+        };
+
+        anext(source.bind(py)).map(Some)
+    }
+
+    /// Runs `x` through the stages from `stage` onwards, stopping early if a callback returns an
+    /// awaitable. Returns the result if that reaches the goal.
+    fn run<'py>(
+        &mut self,
+        py: Python<'py>,
+        mut x: Bound<'py, PyAny>,
+        mut stage: usize,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        loop {
+            // callbacks are called without the iterator borrowed, in case they use it
+            let (f, filter) = {
+                let mut iter = self.iter.get().iter.lock(py)?;
+                match iter.stages.get_mut(stage) {
+                    None => break,
+                    Some(AsyncStage::Map(f)) => (f.clone_ref(py), false),
+                    Some(AsyncStage::Filter(f)) => (f.clone_ref(py), true),
+                    Some(AsyncStage::Enumerate(i)) => {
+                        x = (*i, x).into_bound_py_any(py)?;
+                        *i += 1;
+                        stage += 1;
+                        continue;
+                    }
+                    Some(AsyncStage::Take(0)) => {
+                        drop(iter);
+                        return self.exhausted(py).map(Some);
+                    }
+                    Some(AsyncStage::Take(n)) => {
+                        *n -= 1;
+                        stage += 1;
+                        continue;
+                    }
+                }
+            };
+
+            let result = f.bind(py).call1((&x,))?;
+            if let Some(awaiting) = awaiting(&result)? {
+                let then = if filter {
+                    Then::Filtered(stage, x.unbind())
+                } else {
+                    Then::Mapped(stage)
+                };
+                self.state = State::Awaiting {
+                    awaiting: awaiting.unbind(),
+                    then,
+                };
+                return Ok(None);
+            }
+
+            if !filter {
+                x = result;
+            } else if !result.is_truthy()? {
+                self.state = State::Start;
+                return Ok(None);
+            }
+            stage += 1;
+        }
+
+        self.deliver(py, x)
+    }
+
+    fn deliver<'py>(
+        &mut self,
+        py: Python<'py>,
+        x: Bound<'py, PyAny>,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        self.state = State::Start;
+        match &mut self.goal {
+            Goal::Next => {
+                self.state = State::Finished;
+                return Ok(Some(x));
+            }
+            Goal::ToList(v) => v.push(x.unbind()),
+            Goal::Fold { acc, f } => {
+                let result = f.bind(py).call1((acc.bind(py), x))?;
+                if let Some(awaiting) = awaiting(&result)? {
+                    self.state = State::Awaiting {
+                        awaiting: awaiting.unbind(),
+                        then: Then::Folded,
+                    };
+                } else {
+                    *acc = result.unbind();
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Resolves the goal once the source runs out
+    fn exhausted<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.state = State::Finished;
+        match &mut self.goal {
+            Goal::Next => Err(PyStopAsyncIteration::new_err(())),
+            Goal::ToList(v) => Ok(PyList::new(py, std::mem::take(v))?.into_any()),
+            Goal::Fold { acc, .. } => Ok(acc.bind(py).clone()),
+        }
+    }
+}
+
+impl Coroutine for Driver {
+    fn step<'py>(&mut self, py: Python<'py>, mut input: Input<'py>) -> PyResult<Bound<'py, PyAny>> {
+        loop {
+            let result = match std::mem::replace(&mut self.state, State::Finished) {
+                State::Finished => {
+                    return Err(PyRuntimeError::new_err(
+                        "cannot reuse already awaited awaitable",
+                    ));
+                }
+                State::Start => {
+                    if let Input::Throw(e) = input {
+                        return Err(e);
+                    }
+                    match self.pull(py)? {
+                        Some(awaiting) => {
+                            self.state = State::Awaiting {
+                                awaiting: awaiting.unbind(),
+                                then: Then::Pulled,
+                            };
+                            None
+                        }
+                        None => Some(self.exhausted(py)?),
+                    }
+                }
+                State::Awaiting { awaiting, then } => {
+                    let awaiting = awaiting.into_bound(py);
+                    let input =
+                        std::mem::replace(&mut input, Input::Send(py.None().into_bound(py)));
+                    let value = match resume(&awaiting, input) {
+                        Ok(Poll::Yield(y)) => {
+                            self.state = State::Awaiting {
+                                awaiting: awaiting.unbind(),
+                                then,
+                            };
+                            return Ok(y);
+                        }
+                        Ok(Poll::Ready(value)) => value,
+                        Err(e)
+                            if matches!(then, Then::Pulled)
+                                && e.is_instance_of::<PyStopAsyncIteration>(py) =>
+                        {
+                            return Err(ready(self.exhausted(py)?));
+                        }
+                        Err(e) => return Err(e),
+                    };
+
+                    match then {
+                        Then::Pulled => self.run(py, value, 0)?,
+                        Then::Mapped(stage) => self.run(py, value, stage + 1)?,
+                        Then::Filtered(stage, x) => {
+                            if value.is_truthy()? {
+                                self.run(py, x.into_bound(py), stage + 1)?
+                            } else {
+                                self.state = State::Start;
+                                None
+                            }
+                        }
+                        Then::Folded => {
+                            if let Goal::Fold { acc, .. } = &mut self.goal {
+                                *acc = value.unbind();
+                            }
+                            self.state = State::Start;
+                            None
+                        }
+                    }
+                }
+            };
+
+            if let Some(result) = result {
+                self.state = State::Finished;
+                return Err(ready(result));
+            }
+        }
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        if let State::Awaiting { awaiting, .. } =
+            std::mem::replace(&mut self.state, State::Finished)
+        {
+            close(awaiting.bind(py))?;
+        }
+        Ok(())
+    }
+}
+
+/// The awaitable returned by `__anext__` and the terminals of an async iterator, driving its
+/// coroutine as `yield from` would a generator
+#[pyclass]
+pub struct PyAwaitable {
+    coroutine: Box<dyn Coroutine>,
+}
+
+impl PyAwaitable {
+    pub fn new(coroutine: impl Coroutine + 'static) -> Self {
+        Self {
+            coroutine: Box::new(coroutine),
+        }
+    }
+}
+
+#[pymethods]
+impl PyAwaitable {
+    pub fn __await__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    pub fn __iter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    pub fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.coroutine
+            .step(py, Input::Send(py.None().into_bound(py)))
+    }
+
+    pub fn send<'py>(&mut self, value: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        self.coroutine.step(value.py(), Input::Send(value))
+    }
+
+    #[pyo3(signature = (typ, val = None, _tb = None))]
+    pub fn throw<'py>(
+        &mut self,
+        typ: Bound<'py, PyAny>,
+        val: Option<Bound<'py, PyAny>>,
+        _tb: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let py = typ.py();
+        let e = match val.filter(|val| !val.is_none()) {
+            Some(val) if val.is_instance_of::<PyBaseException>() => PyErr::from_value(val),
+            Some(val) => PyErr::from_value(typ.call1((val,))?),
+            None => PyErr::from_value(typ),
+        };
+        self.coroutine.step(py, Input::Throw(e))
+    }
+
+    pub fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.coroutine.close(py)
+    }
+}
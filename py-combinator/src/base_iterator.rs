@@ -1,33 +1,291 @@
-#[pyo3::pyclass(generic)]
+pub type PyBaseIteratorT = Box<dyn crate::py_iter::PyIter>;
+#[pyo3::pyclass(frozen, generic)]
 pub struct PyBaseIterator {
-    iter: Box<dyn Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>> + Send + Sync>,
+    iter: crate::exclusive::Exclusive<PyBaseIteratorT>,
 }
 
 impl PyBaseIterator {
-    pub fn new(
-        iter: Box<dyn Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>> + Send + Sync>,
-    ) -> Self {
-        Self { iter }
+    pub fn new(iter: PyBaseIteratorT) -> Self {
+        Self {
+            iter: crate::exclusive::Exclusive::new(iter),
+        }
+    }
+}
+
+impl crate::exclusive::Guarded for PyBaseIterator {
+    type Inner = PyBaseIteratorT;
+
+    fn guarded(&self) -> &crate::exclusive::Exclusive<PyBaseIteratorT> {
+        &self.iter
+    }
+}
+
+/// Clamps the lower bound of a size hint to what Python accepts from `__length_hint__`
+pub fn length_hint((lower, _): (usize, Option<usize>)) -> usize {
+    lower.min(isize::MAX.unsigned_abs())
+}
+
+/// Returns the length of a sized iterator from its size hint, which only lacks an upper bound if
+/// the length overflowed
+pub fn exact_len(hint: (usize, Option<usize>)) -> pyo3::PyResult<usize> {
+    match hint {
+        (lower, Some(upper)) if lower == upper => Ok(lower),
+        _ => Err(pyo3::exceptions::PyOverflowError::new_err(
+            "the length of the iterator is too large to be counted",
+        )),
     }
 }
 
 #[macros::register_methods(self_generic = S)]
 impl crate::base_iterator::PyBaseIterator {
     #[macros::method_self_arg]
-    pub fn take_inner(
-        &mut self,
-    ) -> Box<dyn Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>> + Send + Sync> {
-        std::mem::replace(&mut self.iter, Box::new(std::iter::empty()))
+    pub fn take_inner(&self) -> pyo3::PyResult<PyBaseIteratorT> {
+        pyo3::Python::with_gil(|py| {
+            Ok(std::mem::replace(
+                &mut *self.iter.lock(py)?,
+                Box::new(std::iter::empty()),
+            ))
+        })
     }
 
     #[doc = "Converts the iterator to a list"]
     #[macros::return_literal]
-    pub fn to_list<S>(iter: S) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyList>>
+    pub fn to_list<S>(mut iter: S) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyList>>
     where
-        S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| {
+            // the lower bound may come from an arbitrary `__length_hint__`, so failing to reserve
+            // it isn't an error
+            let mut v = Vec::new();
+            v.try_reserve(iter.size_hint().0).ok();
+            for x in iter.bound(py) {
+                v.push(x?);
+            }
+            Ok(pyo3::types::PyList::new(py, v)?.unbind())
+        })
+    }
+
+    #[doc = "Converts the iterator to a tuple"]
+    #[macros::return_literal]
+    pub fn to_tuple<S>(mut iter: S) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyTuple>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| {
+            let v = iter.bound(py).collect::<pyo3::PyResult<Vec<_>>>()?;
+            Ok(pyo3::types::PyTuple::new(py, v)?.unbind())
+        })
+    }
+
+    #[doc = "Converts the iterator to a set"]
+    #[macros::return_literal]
+    pub fn to_set<S>(mut iter: S) -> pyo3::PyResult<pyo3::Py<pyo3::types::PySet>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| {
+            let v = iter.bound(py).collect::<pyo3::PyResult<Vec<_>>>()?;
+            Ok(pyo3::types::PySet::new(py, v)?.unbind())
+        })
+    }
+
+    #[doc = "Converts the iterator to a frozenset"]
+    #[macros::return_literal]
+    pub fn to_frozenset<S>(mut iter: S) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyFrozenSet>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| {
+            let v = iter.bound(py).collect::<pyo3::PyResult<Vec<_>>>()?;
+            Ok(pyo3::types::PyFrozenSet::new(py, v)?.unbind())
+        })
+    }
+
+    #[macros::return_literal]
+    #[macros::signature(on_duplicate = crate::arguments::OnDuplicate::Last)]
+    #[doc = "Converts an iterator of `(key, value)` pairs to a dict. When a key
+             appears more than once, `on_duplicate` decides the outcome: `'last'`
+             keeps the latest value, `'first'` keeps the earliest one and `'error'`
+             raises a `KeyError`.
+             
+             Examples:
+                 iter # [('a', 1), ('b', 2), ('a', 3)]
+                 iter.to_dict() # {'a': 3, 'b': 2}
+                 iter.to_dict(on_duplicate='first') # {'a': 1, 'b': 2}"]
+    pub fn to_dict<S>(
+        mut iter: S,
+        on_duplicate: crate::arguments::OnDuplicate,
+    ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyDict>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        use pyo3::types::{PyAnyMethods, PyDictMethods};
+
+        pyo3::Python::with_gil(|py| {
+            let dict = pyo3::types::PyDict::new(py);
+
+            for (i, x) in iter.bound(py).enumerate() {
+                let pair = x?.try_iter()?.collect::<pyo3::PyResult<Vec<_>>>()?;
+                let [key, value] = <[_; 2]>::try_from(pair).map_err(|pair| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "dictionary update sequence element #{i} has length {}; 2 is required",
+                        pair.len()
+                    ))
+                })?;
+
+                if on_duplicate != crate::arguments::OnDuplicate::Last && dict.contains(&key)? {
+                    if on_duplicate == crate::arguments::OnDuplicate::Error {
+                        return Err(pyo3::exceptions::PyKeyError::new_err(key.unbind()));
+                    }
+                } else {
+                    dict.set_item(key, value)?;
+                }
+            }
+
+            Ok(dict.unbind())
+        })
+    }
+
+    #[macros::return_literal]
+    #[macros::signature(maxlen = None)]
+    #[doc = "Converts the iterator to a `collections.deque`. With `maxlen`, only
+             the last `maxlen` elements are kept.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.to_deque(maxlen=2) # deque([2, 3], maxlen=2)"]
+    pub fn to_deque<S>(
+        mut iter: S,
+        maxlen: Option<usize>,
+    ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        use pyo3::types::PyAnyMethods;
+
+        pyo3::Python::with_gil(|py| {
+            let mut v = std::collections::VecDeque::new();
+            for x in iter.bound(py) {
+                let x = x?;
+                if maxlen.is_some_and(|m| v.len() >= m) {
+                    v.pop_front();
+                }
+                if maxlen != Some(0) {
+                    v.push_back(x);
+                }
+            }
+
+            Ok(py
+                .import("collections")?
+                .getattr("deque")?
+                .call1((pyo3::types::PyList::new(py, v)?, maxlen))?
+                .unbind())
+        })
+    }
+
+    #[macros::return_literal]
+    #[doc = "Concatenates an iterator of strings, placing `sep` between each of them.
+             
+             Examples:
+                 iter # ['a', 'b', 'c']
+                 iter.join(', ') # 'a, b, c'"]
+    pub fn join<S>(mut iter: S, sep: &str) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyString>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        use pyo3::types::{PyAnyMethods, PyStringMethods, PyTypeMethods};
+
+        pyo3::Python::with_gil(|py| {
+            let mut joined = String::new();
+
+            for (i, x) in iter.bound(py).enumerate() {
+                let x = x?;
+                let s = x.downcast::<pyo3::types::PyString>().map_err(|_| {
+                    pyo3::exceptions::PyTypeError::new_err(format!(
+                        "sequence item {i}: expected str instance, {} found",
+                        x.get_type()
+                            .name()
+                            .map_or_else(|_| "?".into(), |n| n.to_string())
+                    ))
+                })?;
+
+                if i > 0 {
+                    joined.push_str(sep);
+                }
+                joined.push_str(&s.to_cow()?);
+            }
+
+            Ok(pyo3::types::PyString::new(py, &joined).unbind())
+        })
+    }
+
+    #[macros::return_literal]
+    #[doc = "Converts an iterator of integers in `range(0, 256)` to bytes.
+             
+             Examples:
+                 iter # [104, 105]
+                 iter.to_bytes() # b'hi'"]
+    pub fn to_bytes<S>(mut iter: S) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyBytes>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        use pyo3::types::PyAnyMethods;
+
+        pyo3::Python::with_gil(|py| {
+            let bytes = iter
+                .bound(py)
+                .map(|x| {
+                    let x = x?;
+                    x.extract::<u8>().map_err(|e| {
+                        if x.is_instance_of::<pyo3::types::PyInt>() {
+                            pyo3::exceptions::PyValueError::new_err(
+                                "bytes must be in range(0, 256)",
+                            )
+                        } else {
+                            e
+                        }
+                    })
+                })
+                .collect::<pyo3::PyResult<Vec<_>>>()?;
+
+            Ok(pyo3::types::PyBytes::new(py, &bytes).unbind())
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // for factory
+    #[macros::return_literal]
+    #[doc = "Converts the iterator to an arbitrary collection by calling `factory`
+             with a list of its elements.
+             
+             Examples:
+                 iter # [3, 1, 2]
+                 iter.collect(sorted) # [1, 2, 3]
+                 iter.collect(collections.Counter) # Counter({3: 1, 1: 1, 2: 1})"]
+    pub fn collect<S>(
+        mut iter: S,
+        factory: crate::arguments::Callable,
+    ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| {
+            let v = iter.bound(py).collect::<pyo3::PyResult<Vec<_>>>()?;
+            factory.0.call1(py, (pyo3::types::PyList::new(py, v)?,))
+        })
+    }
+
+    #[macros::return_literal]
+    #[doc = "Consumes the iterator, returning the number of elements.
+             
+             Examples:
+                 iter # [4, 9, 16]
+                 iter.count() # 3"]
+    pub fn count<S>(mut iter: S) -> pyo3::PyResult<usize>
+    where
+        S: crate::py_iter::PyIter,
     {
-        let v = iter.collect::<pyo3::PyResult<Vec<_>>>()?;
-        pyo3::Python::with_gil(|py| Ok(pyo3::types::PyList::new(py, v)?.unbind()))
+        pyo3::Python::with_gil(|py| iter.bound(py).try_fold(0, |n, x| x.map(|_| n + 1)))
     }
 
     #[allow(clippy::needless_pass_by_value)] // for f
@@ -43,32 +301,33 @@ impl crate::base_iterator::PyBaseIterator {
         f: pyo3::Py<pyo3::types::PyFunction>,
     ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
     where
-        S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
+        S: crate::py_iter::PyIter,
     {
+        use pyo3::types::PyAnyMethods;
+
         pyo3::Python::with_gil(|py| {
-            iter.try_fold(init, |a, x| x.and_then(|x| f.call1(py, (&a, x))))
+            let f = f.bind(py);
+            iter.bound(py)
+                .enumerate()
+                .try_fold(init.into_bound(py), |a, (i, x)| {
+                    let x = x?;
+                    f.call1((a, &x))
+                        .map_err(|e| crate::notes::add_note(py, e, "fold", i, &x))
+                })
+                .map(pyo3::Bound::unbind)
         })
     }
 
-    #[allow(clippy::type_complexity)]
     #[doc = "Creates a new iterator by applying `f` to each element.
              
              Examples:
                  iter # [1, 2, 3]
                  iter.map(lambda x: x + 1) # [2, 3, 4]"]
-    pub fn map<S>(
-        iter: S,
-        f: pyo3::Py<pyo3::types::PyFunction>,
-    ) -> std::iter::Map<
-        S,
-        impl FnMut(
-            pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>,
-        ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>,
-    >
+    pub fn map<S>(iter: S, f: pyo3::Py<pyo3::types::PyFunction>) -> crate::pipeline::Pipeline<S>
     where
-        S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
+        S: crate::pipeline::PipelineSource,
     {
-        iter.map(move |x| pyo3::Python::with_gil(|py| x.and_then(|x| f.call1(py, (x.bind(py),)))))
+        crate::pipeline::Pipeline::push(iter, crate::pipeline::Stage::Map(f))
     }
 
     #[doc = "Creates a new iterator that yields elements for which `f` returns `true`.
@@ -77,25 +336,11 @@ impl crate::base_iterator::PyBaseIterator {
                  iter # [1, 2, 3]
                  iter.filter(lambda x: x % 2 == 0) # [2]"]
     #[macros::strips_traits(PyExactSizeIterator)]
-    pub fn filter<S>(
-        iter: S,
-        f: pyo3::Py<pyo3::types::PyFunction>,
-    ) -> std::iter::Filter<S, impl FnMut(&pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>) -> bool>
+    pub fn filter<S>(iter: S, f: pyo3::Py<pyo3::types::PyFunction>) -> crate::pipeline::Pipeline<S>
     where
-        S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
+        S: crate::pipeline::PipelineSource,
     {
-        let bad_predicate = "exception in filter predicate";
-
-        iter.filter(move |x| {
-            pyo3::Python::with_gil(|py| {
-                x.as_ref()
-                    .map(|x| f.call1(py, (x.bind(py),)))
-                    .expect(bad_predicate)
-                    .map(|x| x.is_truthy(py))
-                    .and_then(|x| x)
-                    .expect(bad_predicate)
-            })
-        })
+        crate::pipeline::Pipeline::push(iter, crate::pipeline::Stage::Filter(f))
     }
 
     #[doc = "The iterator returned yields pairs `(i, val)`, where `i` is the
@@ -105,35 +350,589 @@ impl crate::base_iterator::PyBaseIterator {
              Examples:
                  iter # [4, 9, 16]
                  iter.enumerate() # [(0, 4), (1, 9), (2, 16)]"]
-    #[allow(clippy::type_complexity)]
-    pub fn enumerate<S>(
+    pub fn enumerate<S>(iter: S) -> crate::pipeline::Pipeline<S>
+    where
+        S: crate::pipeline::PipelineSource,
+    {
+        crate::pipeline::Pipeline::push(iter, crate::pipeline::Stage::Enumerate(0))
+    }
+
+    #[doc = "Creates a new iterator over the first `n` elements of the iterator.
+             Like every adapter, it leaves the iterator empty, rather than taking
+             only the first `n` elements out of it.
+             
+             Examples:
+                 iter # [4, 9, 16]
+                 iter.take(2) # [4, 9]
+                 iter # []"]
+    pub fn take<S>(iter: S, n: usize) -> crate::pipeline::Pipeline<S>
+    where
+        S: crate::pipeline::PipelineSource,
+    {
+        crate::pipeline::Pipeline::push(iter, crate::pipeline::Stage::Take(n))
+    }
+
+    #[doc = "Creates a new iterator that skips the first `n` elements of the iterator,
+             which are pulled and dropped once the first element is asked for.
+             
+             Examples:
+                 iter # [4, 9, 16]
+                 iter.skip(2) # [16]"]
+    pub fn skip<S>(iter: S, n: usize) -> crate::pipeline::Pipeline<S>
+    where
+        S: crate::pipeline::PipelineSource,
+    {
+        crate::pipeline::Pipeline::push(iter, crate::pipeline::Stage::Skip(n))
+    }
+
+    #[macros::fallible]
+    #[macros::signature(exc_types, handler = None)]
+    #[macros::strips_traits(PyExactSizeIterator)]
+    #[doc = "Creates a new iterator which intercepts exceptions matching
+             `exc_types` raised by the source or any earlier stage, yielding what
+             `handler` returns for them instead, or skipping them without one.
+             Other exceptions are raised as usual.
+             
+             Examples:
+                 iter # ['1', 'x', '3']
+                 iter.map(lambda x: int(x)).catch(ValueError) # [1, 3]
+                 iter.map(lambda x: int(x)).catch(ValueError, lambda e: 0) # [1, 0, 3]"]
+    pub fn catch<S>(
+        iter: S,
+        exc_types: pyo3::Py<pyo3::types::PyAny>,
+        handler: Option<pyo3::Py<pyo3::types::PyFunction>>,
+    ) -> pyo3::PyResult<crate::pipeline::Pipeline<S>>
+    where
+        S: crate::pipeline::PipelineSource,
+    {
+        pyo3::Python::with_gil(|py| crate::pipeline::exception_types(exc_types.bind(py)))?;
+        Ok(crate::pipeline::Pipeline::push(
+            iter,
+            crate::pipeline::Stage::Catch {
+                types: exc_types,
+                handler,
+            },
+        ))
+    }
+
+    #[macros::fallible]
+    #[macros::signature(f, workers = None, ordered = true, chunk_size = 64)]
+    #[macros::strips_traits(PyDoubleEndedIterator)]
+    #[doc = "Creates a new iterator by applying `f` to each element on `workers`
+             threads, which default to the number of CPUs and are kept until the
+             iterator is dropped. Elements are handed to them `chunk_size` at a
+             time, up to `chunk_size` per worker ahead of the next one yielded.
+             Each call takes the GIL on its own, so `f` runs in parallel when it
+             releases the GIL itself or the interpreter is free-threaded. With
+             `ordered=False`, results are yielded in the order they're done.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.par_map(lambda x: x + 1, workers=2) # [2, 3, 4]"]
+    pub fn par_map<S>(
+        iter: S,
+        f: pyo3::Py<pyo3::types::PyFunction>,
+        workers: Option<usize>,
+        ordered: bool,
+        chunk_size: usize,
+    ) -> pyo3::PyResult<crate::parallel::ParMap<S>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        crate::parallel::ParMap::new(iter, f, workers, ordered, chunk_size)
+    }
+
+    #[macros::strips_traits(PyDoubleEndedIterator)]
+    #[doc = "Creates a new iterator over every `k`-length tuple of elements, in the
+             order of their positions in the original iterator. Elements are
+             never repeated within a tuple.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.combinations(2) # [(1, 2), (1, 3), (2, 3)]"]
+    pub fn combinations<S>(iter: S, k: usize) -> crate::combinatorics::Combinatoric<S>
+    where
+        S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
+    {
+        crate::combinatorics::Combinatoric::new(
+            iter,
+            crate::combinatorics::Selection::Combinations(k),
+        )
+    }
+
+    #[macros::strips_traits(PyDoubleEndedIterator)]
+    #[doc = "Creates a new iterator over every `k`-length tuple of elements, in the
+             order of their positions in the original iterator. Elements may be
+             repeated within a tuple.
+             
+             Examples:
+                 iter # [1, 2]
+                 iter.combinations_with_replacement(2) # [(1, 1), (1, 2), (2, 2)]"]
+    pub fn combinations_with_replacement<S>(
+        iter: S,
+        k: usize,
+    ) -> crate::combinatorics::Combinatoric<S>
+    where
+        S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
+    {
+        crate::combinatorics::Combinatoric::new(
+            iter,
+            crate::combinatorics::Selection::CombinationsWithReplacement(k),
+        )
+    }
+
+    #[macros::signature(k = None)]
+    #[macros::strips_traits(PyDoubleEndedIterator)]
+    #[doc = "Creates a new iterator over every ordering of `k` distinct elements,
+             where `k` defaults to the length of the iterator.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.permutations(2) # [(1, 2), (1, 3), (2, 1), (2, 3), (3, 1), (3, 2)]"]
+    pub fn permutations<S>(iter: S, k: Option<usize>) -> crate::combinatorics::Combinatoric<S>
+    where
+        S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
+    {
+        crate::combinatorics::Combinatoric::new(
+            iter,
+            crate::combinatorics::Selection::Permutations(k),
+        )
+    }
+
+    #[macros::strips_traits(PyDoubleEndedIterator)]
+    #[doc = "Creates a new iterator over every subset of the elements as a tuple,
+             from smallest to largest.
+             
+             Examples:
+                 iter # [1, 2]
+                 iter.powerset() # [(), (1,), (2,), (1, 2)]"]
+    pub fn powerset<S>(iter: S) -> crate::combinatorics::Combinatoric<S>
+    where
+        S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
+    {
+        crate::combinatorics::Combinatoric::new(iter, crate::combinatorics::Selection::Powerset)
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // for others
+    #[macros::fallible]
+    #[macros::signature(*others)]
+    #[macros::strips_traits(PyDoubleEndedIterator)]
+    #[doc = "Creates a new iterator over tuples pairing each element with every
+             combination of elements from `others`.
+             
+             Examples:
+                 iter # [1, 2]
+                 iter.cartesian_product('ab') # [(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]"]
+    pub fn cartesian_product<S>(
         iter: S,
-    ) -> std::iter::Map<
-        std::iter::Enumerate<S>,
-        impl FnMut(
-            (usize, pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>),
-        ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>,
-    >
+        others: Vec<crate::arguments::Iterable>,
+    ) -> pyo3::PyResult<crate::combinatorics::CartesianProduct<S>>
     where
         S: Iterator<Item = pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>>,
     {
-        use pyo3::IntoPyObjectExt;
-        iter.enumerate()
-            .map(move |(i, x)| pyo3::Python::with_gil(|py| x.and_then(|x| (i, x).into_py_any(py))))
+        crate::combinatorics::CartesianProduct::new(iter, &others)
+    }
+
+    #[macros::return_literal]
+    #[doc = "Creates a new iterator repeating the elements of this one forever. The
+             elements are remembered during the first pass, so the iterator itself
+             is only consumed once.
+             
+             Examples:
+                 iter # [1, 2]
+                 iter.cycle() # [1, 2, 1, 2, 1, ...]"]
+    pub fn cycle<S>(iter: S) -> pyo3::PyResult<pyo3::Py<crate::iterators::PyInfiniteIterator>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| {
+            pyo3::Py::new(
+                py,
+                crate::iterators::PyInfiniteIterator::new(Box::new(crate::sources::Cycle::new(
+                    iter,
+                ))),
+            )
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // for other
+    #[macros::return_literal]
+    #[macros::strips_traits(PyDoubleEndedIterator, PyExactSizeIterator)]
+    #[doc = "Creates a new iterator alternating between elements of this iterator
+             and `other`, continuing with the longer one once the other runs out.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.interleave([4, 5]) # [1, 4, 2, 5, 3]"]
+    pub fn interleave<S>(
+        iter: S,
+        other: crate::arguments::Iterable,
+    ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| crate::interleaving::interleave(iter, other.0.bind(py), false))
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // for other
+    #[macros::return_literal]
+    #[macros::strips_traits(PyDoubleEndedIterator, PyExactSizeIterator)]
+    #[doc = "Creates a new iterator alternating between elements of this iterator
+             and `other`, stopping as soon as either runs out.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.interleave_shortest([4, 5]) # [1, 4, 2, 5, 3]
+                 iter.interleave_shortest([4]) # [1, 4, 2]"]
+    pub fn interleave_shortest<S>(
+        iter: S,
+        other: crate::arguments::Iterable,
+    ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| crate::interleaving::interleave(iter, other.0.bind(py), true))
+    }
+
+    #[macros::strips_traits(PyDoubleEndedIterator)]
+    #[doc = "Creates a new iterator placing `sep` between every pair of adjacent
+             elements.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.intersperse(0) # [1, 0, 2, 0, 3]"]
+    pub fn intersperse<S>(
+        iter: S,
+        sep: pyo3::Py<pyo3::types::PyAny>,
+    ) -> crate::interleaving::Intersperse<S>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        crate::interleaving::Intersperse::new(iter, crate::interleaving::Separator::Value(sep))
+    }
+
+    #[macros::strips_traits(PyDoubleEndedIterator)]
+    #[doc = "Creates a new iterator placing the result of calling `f` between every
+             pair of adjacent elements.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.intersperse_with(lambda: 0) # [1, 0, 2, 0, 3]"]
+    pub fn intersperse_with<S>(
+        iter: S,
+        f: pyo3::Py<pyo3::types::PyFunction>,
+    ) -> crate::interleaving::Intersperse<S>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        crate::interleaving::Intersperse::new(iter, crate::interleaving::Separator::With(f))
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // for other
+    #[macros::return_literal]
+    #[macros::signature(other, key = None)]
+    #[macros::strips_traits(PyDoubleEndedIterator, PyExactSizeIterator)]
+    #[doc = "Creates a new iterator merging this iterator and `other`, both assumed
+             to be sorted, into a single sorted iterator. Elements are compared by
+             `key` if given, and ties are yielded from this iterator first.
+             
+             Examples:
+                 iter # [1, 3, 5]
+                 iter.merge([2, 3, 4]) # [1, 2, 3, 3, 4, 5]"]
+    pub fn merge<S>(
+        iter: S,
+        other: crate::arguments::Iterable,
+        key: Option<pyo3::Py<pyo3::types::PyFunction>>,
+    ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| crate::interleaving::kmerge(py, iter, &[other], key))
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // for others
+    #[macros::return_literal]
+    #[macros::signature(*others, key = None)]
+    #[macros::strips_traits(PyDoubleEndedIterator, PyExactSizeIterator)]
+    #[doc = "Creates a new iterator merging this iterator and every one of `others`,
+             all assumed to be sorted, into a single sorted iterator. Elements are
+             compared by `key` if given, and ties are yielded in argument order.
+             
+             Examples:
+                 iter # [1, 4]
+                 iter.kmerge([2, 5], [3, 6]) # [1, 2, 3, 4, 5, 6]"]
+    pub fn kmerge<S>(
+        iter: S,
+        others: Vec<crate::arguments::Iterable>,
+        key: Option<pyo3::Py<pyo3::types::PyFunction>>,
+    ) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyAny>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| crate::interleaving::kmerge(py, iter, &others, key))
+    }
+
+    #[macros::return_literal]
+    #[doc = "Consumes the iterator, collecting the elements it yields and the
+             exceptions it raises along the way into separate lists.
+             
+             Examples:
+                 iter # ['1', 'x', '3']
+                 iter.map(lambda x: int(x)).collect_errors() # ([1, 3], [ValueError(...)])"]
+    pub fn collect_errors<S>(
+        mut iter: S,
+    ) -> pyo3::PyResult<(pyo3::Py<pyo3::types::PyList>, pyo3::Py<pyo3::types::PyList>)>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| {
+            let mut values = Vec::new();
+            let mut errors = Vec::new();
+
+            // exceptions raised by signal handlers abort instead of being collected
+            let mut iter = iter.bound(py);
+            while let Some(x) = iter.next_element()? {
+                match x {
+                    Ok(x) => values.push(x),
+                    Err(e) => errors.push(e.into_value(py)),
+                }
+            }
+
+            Ok((
+                pyo3::types::PyList::new(py, values)?.unbind(),
+                pyo3::types::PyList::new(py, errors)?.unbind(),
+            ))
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // for f
+    #[macros::return_literal]
+    #[doc = "Consumes the iterator, splitting it into the elements for which `f`
+             returns `true` and the elements for which it returns `false`.
+             
+             Examples:
+                 iter # [1, 2, 3, 4]
+                 iter.partition(lambda x: x % 2 == 0) # ([2, 4], [1, 3])"]
+    pub fn partition<S>(
+        mut iter: S,
+        f: pyo3::Py<pyo3::types::PyFunction>,
+    ) -> pyo3::PyResult<(pyo3::Py<pyo3::types::PyList>, pyo3::Py<pyo3::types::PyList>)>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        use pyo3::types::PyAnyMethods;
+
+        pyo3::Python::with_gil(|py| {
+            let f = f.bind(py);
+            let mut matching = Vec::new();
+            let mut rest = Vec::new();
+
+            for (i, x) in iter.bound(py).enumerate() {
+                let x = x?;
+                let matches = f
+                    .call1((&x,))
+                    .and_then(|m| m.is_truthy())
+                    .map_err(|e| crate::notes::add_note(py, e, "partition", i, &x))?;
+                if matches {
+                    matching.push(x);
+                } else {
+                    rest.push(x);
+                }
+            }
+
+            Ok((
+                pyo3::types::PyList::new(py, matching)?.unbind(),
+                pyo3::types::PyList::new(py, rest)?.unbind(),
+            ))
+        })
+    }
+
+    #[macros::return_literal]
+    #[doc = "Consumes an iterator of `n`-tuples, producing a tuple of `n` lists
+             where the `i`th list holds the `i`th item of every element.
+             
+             Examples:
+                 iter # [(1, 'a'), (2, 'b')]
+                 iter.unzip() # ([1, 2], ['a', 'b'])"]
+    pub fn unzip<S>(mut iter: S) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyTuple>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        use pyo3::types::PyAnyMethods;
+        pyo3::Python::with_gil(|py| {
+            let mut columns: Option<Vec<Vec<pyo3::Bound<'_, pyo3::types::PyAny>>>> = None;
+
+            for x in iter.bound(py) {
+                let row = x?.try_iter()?.collect::<pyo3::PyResult<Vec<_>>>()?;
+
+                let columns = columns.get_or_insert_with(|| {
+                    std::iter::repeat_with(Vec::new).take(row.len()).collect()
+                });
+
+                if row.len() != columns.len() {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "unzip expected elements of length {}, got one of length {}",
+                        columns.len(),
+                        row.len(),
+                    )));
+                }
+
+                for (column, item) in columns.iter_mut().zip(row) {
+                    column.push(item);
+                }
+            }
+
+            let columns = columns.unwrap_or_else(|| vec![Vec::new(), Vec::new()]);
+
+            Ok(pyo3::types::PyTuple::new(
+                py,
+                columns
+                    .into_iter()
+                    .map(|column| pyo3::types::PyList::new(py, column))
+                    .collect::<pyo3::PyResult<Vec<_>>>()?,
+            )?
+            .unbind())
+        })
+    }
+
+    #[macros::return_literal]
+    #[doc = "Splits the iterator into `n` independent iterators. Elements are
+             pulled from the original iterator lazily and buffered until every
+             branch has yielded them.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 a, b = iter.tee(2)
+                 a.map(lambda x: x * 2) # [2, 4, 6]
+                 b # [1, 2, 3]"]
+    pub fn tee<S>(iter: S, n: usize) -> pyo3::PyResult<pyo3::Py<pyo3::types::PyTuple>>
+    where
+        S: crate::py_iter::PyIter,
+    {
+        pyo3::Python::with_gil(|py| {
+            crate::tee_iterator::branches(
+                py,
+                Box::new(iter),
+                n,
+                crate::iterators::PyBaseIterator::new,
+            )
+        })
     }
 }
 
 #[macros::add_trait_methods(PyBaseIterator)]
 #[pyo3::pymethods]
 impl PyBaseIterator {
-    #[doc = "Consumes the first `n` elements of the iterator.
+    pub fn __iter__(slf: pyo3::Py<Self>) -> pyo3::Py<Self> {
+        slf
+    }
+
+    pub fn __next__(slf: &pyo3::Bound<'_, Self>) -> pyo3::PyResult<Option<pyo3::Py<pyo3::PyAny>>> {
+        use crate::py_iter::PyIter;
+
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(it.next_with(slf.py()).transpose()?.map(pyo3::Bound::unbind))
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // required by pyo3
+    pub fn __traverse__(&self, visit: pyo3::PyVisit<'_>) -> Result<(), pyo3::PyTraverseError> {
+        use crate::py_iter::PyIter;
+
+        // skipped while the iterator is in use, which only keeps its references alive longer
+        if let Some(iter) = self.iter.try_lock() {
+            iter.traverse(&visit)?;
+        }
+        Ok(())
+    }
+
+    pub fn __clear__(&self) {
+        let iter = self
+            .iter
+            .try_lock()
+            .map(|mut iter| std::mem::replace(&mut *iter, Box::new(std::iter::empty())));
+        drop(iter);
+    }
+
+    pub fn __enter__(slf: pyo3::Py<Self>) -> pyo3::Py<Self> {
+        slf
+    }
+
+    pub fn __exit__(
+        slf: &pyo3::Bound<'_, Self>,
+        _exc_type: &pyo3::Bound<'_, pyo3::PyAny>,
+        _exc_value: &pyo3::Bound<'_, pyo3::PyAny>,
+        _traceback: &pyo3::Bound<'_, pyo3::PyAny>,
+    ) -> pyo3::PyResult<()> {
+        Self::close(slf)
+    }
+
+    #[doc = "Closes the iterator, along with the generators and other closable iterators
+             it's built on, leaving it empty. Called on leaving a `with` block over it.
              
              Examples:
-                 iter # [4, 9, 16]
-                 iter.take(2) # [4, 9]"]
-    pub fn take(&mut self, n: usize) -> Self {
-        Self::new(Box::new(
-            self.iter.by_ref().take(n).collect::<Vec<_>>().into_iter(),
-        ))
+                 iter # [1, 2, 3]
+                 iter.close()
+                 iter # []"]
+    pub fn close(slf: &pyo3::Bound<'_, Self>) -> pyo3::PyResult<()> {
+        use crate::py_iter::PyIter;
+
+        slf.get().take_inner()?.close(slf.py())
+    }
+
+    #[doc = "Returns a copy of the iterator, which yields the remaining elements independently
+             of it and without buffering them as `tee` does. Raises `TypeError` naming the
+             stage that can't be copied, such as a generator source or `par_map`.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 copy = iter.clone()
+                 next(iter) # 1
+                 copy # [1, 2, 3]"]
+    pub fn clone(slf: &pyo3::Bound<'_, Self>) -> pyo3::PyResult<Self> {
+        use crate::py_iter::PyIter;
+
+        crate::exclusive::exclusively(slf, |it| Ok(Self::new(it.try_clone(slf.py())?)))
+    }
+
+    pub fn __copy__(slf: &pyo3::Bound<'_, Self>) -> pyo3::PyResult<Self> {
+        Self::clone(slf)
+    }
+
+    #[doc = "Returns the position of the iterator as plain Python objects, from which
+             `from_state` rebuilds it, in another process too once pickled. Raises
+             `TypeError` naming the stage whose progress can't be saved, such as a
+             generator source or `par_map`.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 next(iter) # 1
+                 from_state(iter.state()) # [2, 3]"]
+    pub fn state(slf: &pyo3::Bound<'_, Self>) -> pyo3::PyResult<pyo3::Py<pyo3::PyAny>> {
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(crate::state::save(slf.py(), "base", &*it)?.unbind())
+        })
+    }
+
+    pub fn __reduce__<'py>(
+        slf: &pyo3::Bound<'py, Self>,
+    ) -> pyo3::PyResult<pyo3::Bound<'py, pyo3::types::PyTuple>> {
+        crate::state::reduce(slf.py(), Self::state(slf)?.into_bound(slf.py()))
+    }
+
+    pub fn __length_hint__(slf: &pyo3::Bound<'_, Self>) -> pyo3::PyResult<usize> {
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(crate::base_iterator::length_hint(it.size_hint()))
+        })
+    }
+
+    #[doc = "Returns the bounds on the remaining length of the iterator, as a
+             `(lower, upper)` tuple where `upper` is `None` if unknown.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.filter(lambda x: x > 1).size_hint() # (0, 3)"]
+    pub fn size_hint(slf: &pyo3::Bound<'_, Self>) -> pyo3::PyResult<(usize, Option<usize>)> {
+        crate::exclusive::exclusively(slf, |it| Ok(it.size_hint()))
     }
 }
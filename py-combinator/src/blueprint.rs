@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use pyo3::{
+    PyTraverseError, PyVisit,
+    exceptions::PyTypeError,
+    prelude::*,
+    types::{IntoPyDict, PyDict, PyTuple},
+};
+
+/// An iterator method recorded by a blueprint, and the arguments it's called with
+struct Step {
+    method: &'static str,
+    args: Py<PyTuple>,
+    kwargs: Option<Py<PyDict>>,
+    /// The trait the method is registered on, which its input must have
+    requires: &'static str,
+    /// The traits its output may no longer have
+    strips: &'static [&'static str],
+}
+
+impl Step {
+    fn clone_ref(&self, py: Python<'_>) -> Self {
+        Self {
+            method: self.method,
+            args: self.args.clone_ref(py),
+            kwargs: self.kwargs.as_ref().map(|k| k.clone_ref(py)),
+            requires: self.requires,
+            strips: self.strips,
+        }
+    }
+}
+
+/// A chain of adapters recorded without a source, applied to one each time it's called. Every
+/// method returns a new blueprint, leaving the one it was called on untouched. Iterables passed to
+/// steps, like the `others` of `cartesian_product`, are used every time the blueprint is applied,
+/// so they should be re-iterable.
+#[pyclass]
+#[derive(Default)]
+pub struct PyPipeline {
+    steps: Vec<Step>,
+    /// Every trait stripped so far, and the step that first stripped it
+    stripped: BTreeMap<&'static str, usize>,
+}
+
+impl PyPipeline {
+    fn clone_ref(&self, py: Python<'_>) -> Self {
+        Self {
+            steps: self.steps.iter().map(|s| s.clone_ref(py)).collect(),
+            stripped: self.stripped.clone(),
+        }
+    }
+
+    /// Appends `step`, raising `TypeError` if an earlier step stripped the trait it needs
+    fn push(&mut self, step: Step) -> PyResult<()> {
+        let number = self.steps.len() + 1;
+        if let Some(&stripped_by) = self.stripped.get(step.requires) {
+            return Err(PyTypeError::new_err(format!(
+                "cannot apply {} (step {number}), as step {stripped_by} ({}) strips {}",
+                step.method,
+                self.steps[stripped_by - 1].method,
+                step.requires
+            )));
+        }
+
+        for &t in step.strips {
+            self.stripped.entry(t).or_insert(number);
+        }
+        self.steps.push(step);
+        Ok(())
+    }
+
+    /// Returns a copy of this blueprint followed by a call to `method`, the methods generated
+    /// from the registered iterator methods all going through here
+    fn record(
+        &self,
+        py: Python<'_>,
+        method: &'static str,
+        requires: &'static str,
+        strips: &'static [&'static str],
+        args: Vec<Py<PyAny>>,
+        kwargs: Vec<(&'static str, Py<PyAny>)>,
+    ) -> PyResult<Self> {
+        let kwargs = if kwargs.is_empty() {
+            None
+        } else {
+            Some(kwargs.into_py_dict(py)?.unbind())
+        };
+
+        let mut pipeline = self.clone_ref(py);
+        pipeline.push(Step {
+            method,
+            args: PyTuple::new(py, args)?.unbind(),
+            kwargs,
+            requires,
+            strips,
+        })?;
+        Ok(pipeline)
+    }
+}
+
+#[macros::add_blueprint_methods(PyBaseIterator, PyDoubleEndedIterator)]
+#[pymethods]
+impl PyPipeline {
+    #[new]
+    #[doc = "Creates an empty blueprint, which builds iterators over its sources
+             unchanged.
+             
+             Examples:
+                 Pipeline().map(lambda x: x * 2).take(2)([1, 2, 3]) # [2, 4]"]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[doc = "Builds the most capable iterator over `source`, as `iterator_from` does,
+             and applies every recorded step to it. Steps drop capabilities just as
+             they do on iterators, so a step that needs one its input lacks raises
+             `TypeError`. A step needing one an earlier step strips raises as soon
+             as it's recorded.
+             
+             Examples:
+                 pipeline = Pipeline().map(lambda x: x + 1)
+                 pipeline([1, 2, 3]).rev() # [4, 3, 2]
+                 pipeline(x for x in [1, 2, 3]) # [2, 3, 4]
+                 Pipeline().cycle().rev() # TypeError"]
+    pub fn __call__(&self, source: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+        let py = source.py();
+        let mut it = crate::iterator_from(source, false, "auto", None)?.into_bound(py);
+
+        for (i, step) in self.steps.iter().enumerate() {
+            if !it.hasattr(step.method)? {
+                return Err(PyTypeError::new_err(format!(
+                    "cannot apply {} (step {}) to {}",
+                    step.method,
+                    i + 1,
+                    it.get_type().name()?
+                )));
+            }
+            it = it.call_method(
+                step.method,
+                step.args.bind(py),
+                step.kwargs.as_ref().map(|k| k.bind(py)),
+            )?;
+        }
+
+        Ok(it.unbind())
+    }
+
+    #[doc = "Creates a new blueprint applying the steps of this one, then those of
+             `other`, raising `TypeError` if a step of `other` needs a capability
+             this one strips.
+             
+             Examples:
+                 double = Pipeline().map(lambda x: x * 2)
+                 double.then(Pipeline().filter(lambda x: x > 2))([1, 2, 3]) # [4, 6]"]
+    #[allow(clippy::needless_pass_by_value)] // required by pyo3
+    pub fn then(&self, py: Python<'_>, other: PyRef<'_, Self>) -> PyResult<Self> {
+        let mut pipeline = self.clone_ref(py);
+        for step in &other.steps {
+            pipeline.push(step.clone_ref(py))?;
+        }
+        Ok(pipeline)
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // required by pyo3
+    pub fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        for step in &self.steps {
+            visit.call(&step.args)?;
+            visit.call(&step.kwargs)?;
+        }
+        Ok(())
+    }
+
+    pub fn __clear__(&mut self) {
+        self.steps.clear();
+        self.stripped.clear();
+    }
+}
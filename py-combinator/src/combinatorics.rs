@@ -0,0 +1,427 @@
+use pyo3::{PyTraverseError, PyVisit, exceptions::PyTypeError, prelude::*, types::PyTuple};
+
+use crate::{
+    arguments::Iterable,
+    iterators::PyInfiniteIterator,
+    py_iter::{self, PyIter, SIGNAL_INTERVAL},
+    state,
+};
+
+/// The number of tuples left to yield, known when the length of the source is
+#[derive(Clone, Copy)]
+enum Remaining {
+    Unknown,
+    // too many to fit in a `usize`, which only raises once the length is asked for
+    Overflowed,
+    Exactly(usize),
+}
+
+impl Remaining {
+    fn counted(count: Option<usize>) -> Self {
+        count.map_or(Remaining::Overflowed, Remaining::Exactly)
+    }
+
+    fn decrement(&mut self) {
+        if let Remaining::Exactly(r) = self {
+            *r = r.saturating_sub(1);
+        }
+    }
+
+    // by convention, an overflowing length has `usize::MAX` as its lower bound and no upper one
+    fn size_hint(self) -> (usize, Option<usize>) {
+        match self {
+            Remaining::Unknown => (0, None),
+            Remaining::Overflowed => (usize::MAX, None),
+            Remaining::Exactly(r) => (r, Some(r)),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Selection {
+    Combinations(usize),
+    CombinationsWithReplacement(usize),
+    Permutations(Option<usize>),
+    Powerset,
+}
+
+fn binomial(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+
+    // every partial product is itself a binomial coefficient, so the division is exact
+    (0..k.min(n - k)).try_fold(1usize, |acc, i| {
+        usize::try_from(acc as u128 * (n - i) as u128 / (i + 1) as u128).ok()
+    })
+}
+
+impl Selection {
+    fn name(self) -> &'static str {
+        match self {
+            Selection::Combinations(_) => "combinations",
+            Selection::CombinationsWithReplacement(_) => "combinations_with_replacement",
+            Selection::Permutations(_) => "permutations",
+            Selection::Powerset => "powerset",
+        }
+    }
+
+    fn count(self, n: usize) -> Option<usize> {
+        match self {
+            Selection::Combinations(k) => binomial(n, k),
+            Selection::CombinationsWithReplacement(k) => match (n, k) {
+                (0, 0) => Some(1),
+                (0, _) => Some(0),
+                _ => binomial(n.checked_add(k)? - 1, k),
+            },
+            Selection::Permutations(k) => match k.unwrap_or(n) {
+                k if k > n => Some(0),
+                k => (n - k + 1..=n).try_fold(1usize, usize::checked_mul),
+            },
+            Selection::Powerset => 1usize.checked_shl(u32::try_from(n).ok()?),
+        }
+    }
+}
+
+pub struct Combinatoric<S> {
+    source: Option<S>,
+    pool: Vec<Py<PyAny>>,
+    selection: Selection,
+    r: usize,
+    indices: Vec<usize>,
+    cycles: Vec<usize>,
+    started: bool,
+    done: bool,
+    remaining: Remaining,
+}
+
+impl<S> Combinatoric<S>
+where
+    S: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    pub fn new(source: S, selection: Selection) -> Self {
+        let remaining = match source.size_hint() {
+            (lower, Some(upper)) if lower == upper => Remaining::counted(selection.count(lower)),
+            _ => Remaining::Unknown,
+        };
+
+        Combinatoric {
+            source: Some(source),
+            pool: Vec::new(),
+            selection,
+            r: 0,
+            indices: Vec::new(),
+            cycles: Vec::new(),
+            started: false,
+            done: false,
+            remaining,
+        }
+    }
+
+    fn start(&mut self) -> bool {
+        let n = self.pool.len();
+
+        match self.selection {
+            Selection::Combinations(k) => {
+                self.r = k;
+                self.indices = (0..k).collect();
+                k <= n
+            }
+            Selection::CombinationsWithReplacement(k) => {
+                self.r = k;
+                self.indices = vec![0; k];
+                n > 0 || k == 0
+            }
+            Selection::Permutations(k) => {
+                self.r = k.unwrap_or(n);
+                self.indices = (0..n).collect();
+                self.cycles = (n.saturating_sub(self.r) + 1..=n).rev().collect();
+                self.r <= n
+            }
+            Selection::Powerset => {
+                self.r = 0;
+                true
+            }
+        }
+    }
+
+    fn advance(&mut self) -> bool {
+        let n = self.pool.len();
+        let r = self.r;
+
+        match self.selection {
+            Selection::Combinations(_) | Selection::Powerset => {
+                if let Some(i) = (0..r).rev().find(|&i| self.indices[i] != i + n - r) {
+                    self.indices[i] += 1;
+                    for j in i + 1..r {
+                        self.indices[j] = self.indices[j - 1] + 1;
+                    }
+                    true
+                } else if matches!(self.selection, Selection::Powerset) && r < n {
+                    self.r += 1;
+                    self.indices = (0..self.r).collect();
+                    true
+                } else {
+                    false
+                }
+            }
+            Selection::CombinationsWithReplacement(_) => {
+                if let Some(i) = (0..r).rev().find(|&i| self.indices[i] != n - 1) {
+                    let next = self.indices[i] + 1;
+                    self.indices[i..].fill(next);
+                    true
+                } else {
+                    false
+                }
+            }
+            Selection::Permutations(_) => {
+                for i in (0..r).rev() {
+                    self.cycles[i] -= 1;
+                    if self.cycles[i] == 0 {
+                        self.indices[i..].rotate_left(1);
+                        self.cycles[i] = n - i;
+                    } else {
+                        self.indices.swap(i, n - self.cycles[i]);
+                        return true;
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+impl<S> Iterator for Combinatoric<S>
+where
+    S: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(source) = self.source.take() {
+            match source.collect::<PyResult<Vec<_>>>() {
+                Ok(pool) => self.pool = pool,
+                Err(e) => {
+                    self.done = true;
+                    self.remaining = Remaining::Exactly(0);
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        if self.done {
+            return None;
+        }
+
+        let produced = if self.started {
+            self.advance()
+        } else {
+            self.started = true;
+            self.start()
+        };
+
+        if !produced {
+            self.done = true;
+            return None;
+        }
+
+        self.remaining.decrement();
+
+        Some(Python::with_gil(|py| {
+            PyTuple::new(
+                py,
+                self.indices[..self.r]
+                    .iter()
+                    .map(|&i| self.pool[i].clone_ref(py)),
+            )
+            .map(|t| t.into_any().unbind())
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining.size_hint()
+    }
+}
+
+impl<S> ExactSizeIterator for Combinatoric<S> where S: ExactSizeIterator<Item = PyResult<Py<PyAny>>> {}
+
+impl<S> PyIter for Combinatoric<S>
+where
+    S: PyIter,
+{
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        if let Some(source) = &self.source {
+            source.traverse(visit)?;
+        }
+        self.pool.iter().try_for_each(|x| visit.call(x))
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.source
+            .as_mut()
+            .map_or(Ok(()), |source| source.close(py))
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        self.source
+            .as_ref()
+            .map_or(0, |source| source.stage_count(name))
+    }
+
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable(self.selection.name()))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable(self.selection.name()))
+    }
+}
+
+pub struct CartesianProduct<S> {
+    source: S,
+    pools: Vec<Vec<Py<PyAny>>>,
+    current: Option<Py<PyAny>>,
+    indices: Vec<usize>,
+    remaining: Remaining,
+}
+
+/// Collects one of the iterables a cartesian product is taken with, which are iterated over
+/// many times
+fn pool(other: &Bound<'_, PyAny>) -> PyResult<Vec<Py<PyAny>>> {
+    if other.is_instance_of::<PyInfiniteIterator>() {
+        return Err(PyTypeError::new_err(
+            "cannot take the cartesian product with an infinite iterator",
+        ));
+    }
+
+    // native iterators don't give the interpreter a chance to run signal handlers
+    let mut pool = Vec::new();
+    for (i, x) in other.try_iter()?.enumerate() {
+        if i % SIGNAL_INTERVAL == SIGNAL_INTERVAL - 1 {
+            other.py().check_signals()?;
+        }
+        pool.push(x?.unbind());
+    }
+    Ok(pool)
+}
+
+impl<S> CartesianProduct<S>
+where
+    S: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    pub fn new(source: S, others: &[Iterable]) -> PyResult<Self> {
+        let pools = Python::with_gil(|py| {
+            others
+                .iter()
+                .map(|other| pool(other.0.bind(py)))
+                .collect::<PyResult<Vec<_>>>()
+        })?;
+
+        let remaining = match source.size_hint() {
+            (lower, Some(upper)) if lower == upper => Remaining::counted(
+                pools
+                    .iter()
+                    .try_fold(lower, |acc, pool| acc.checked_mul(pool.len())),
+            ),
+            _ => Remaining::Unknown,
+        };
+
+        Ok(CartesianProduct {
+            source,
+            indices: vec![0; pools.len()],
+            pools,
+            current: None,
+            remaining,
+        })
+    }
+}
+
+impl<S> Iterator for CartesianProduct<S>
+where
+    S: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pools.iter().any(Vec::is_empty) {
+            return None;
+        }
+
+        if self.current.is_none() {
+            match self.source.next()? {
+                Ok(x) => self.current = Some(x),
+                Err(e) => return Some(Err(e)),
+            }
+            self.indices.fill(0);
+        }
+
+        let item = Python::with_gil(|py| {
+            let current = self.current.as_ref()?.clone_ref(py);
+            let row: Vec<_> = std::iter::once(current)
+                .chain(
+                    self.pools
+                        .iter()
+                        .zip(&self.indices)
+                        .map(|(pool, &i)| pool[i].clone_ref(py)),
+                )
+                .collect();
+            Some(PyTuple::new(py, row).map(|t| t.into_any().unbind()))
+        });
+
+        // odometer over the pools, moving on to the next source element once every pool wraps
+        let wrapped = self
+            .pools
+            .iter()
+            .zip(self.indices.iter_mut())
+            .rev()
+            .all(|(pool, i)| {
+                *i = (*i + 1) % pool.len();
+                *i == 0
+            });
+        if wrapped {
+            self.current = None;
+        }
+
+        self.remaining.decrement();
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining.size_hint()
+    }
+}
+
+impl<S> ExactSizeIterator for CartesianProduct<S> where
+    S: ExactSizeIterator<Item = PyResult<Py<PyAny>>>
+{
+}
+
+impl<S> PyIter for CartesianProduct<S>
+where
+    S: PyIter,
+{
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.source.traverse(visit)?;
+        self.pools
+            .iter()
+            .flatten()
+            .try_for_each(|x| visit.call(x))?;
+        visit.call(&self.current)
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.source.close(py)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        self.source.stage_count(name)
+    }
+
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("cartesian_product"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("cartesian_product"))
+    }
+}
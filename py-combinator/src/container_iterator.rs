@@ -0,0 +1,143 @@
+use pyo3::{
+    PyTraverseError, PyVisit,
+    exceptions::PyRuntimeError,
+    prelude::*,
+    types::{PyDict, PyDictItems, PyDictKeys, PyDictValues, PyFrozenSet, PyIterator, PySet},
+};
+
+use crate::{
+    py_iter::{self, PyIter},
+    state,
+};
+
+/// Iterates over a dict, one of its views, or a set, raising if the container changes size
+/// along the way
+pub struct PyContainerIterator {
+    container: Py<PyAny>,
+    kind: &'static str,
+    initial_len: usize,
+    remaining: usize,
+    forward: Py<PyIterator>,
+    // only built once iterating from the back, as sets can't be reversed
+    backward: Option<Py<PyIterator>>,
+}
+
+impl PyContainerIterator {
+    pub fn is_dict_like(obj: &Bound<'_, PyAny>) -> bool {
+        obj.is_instance_of::<PyDict>()
+            || obj.is_instance_of::<PyDictKeys>()
+            || obj.is_instance_of::<PyDictValues>()
+            || obj.is_instance_of::<PyDictItems>()
+    }
+
+    pub fn is_set_like(obj: &Bound<'_, PyAny>) -> bool {
+        obj.is_instance_of::<PySet>() || obj.is_instance_of::<PyFrozenSet>()
+    }
+
+    pub fn new(container: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let len = container.len()?;
+        Ok(PyContainerIterator {
+            container: container.clone().unbind(),
+            kind: if Self::is_set_like(container) {
+                "Set"
+            } else {
+                "dictionary"
+            },
+            initial_len: len,
+            remaining: len,
+            forward: container.try_iter()?.unbind(),
+            backward: None,
+        })
+    }
+
+    fn check_size(&self, py: Python<'_>) -> PyResult<()> {
+        if self.container.bind(py).len()? == self.initial_len {
+            Ok(())
+        } else {
+            Err(PyRuntimeError::new_err(format!(
+                "{} changed size during iteration",
+                self.kind
+            )))
+        }
+    }
+
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        backward: bool,
+    ) -> Option<PyResult<Bound<'py, PyAny>>> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        if let Err(e) = self.check_size(py) {
+            self.remaining = 0;
+            return Some(Err(e));
+        }
+
+        let iter = if backward {
+            if self.backward.is_none() {
+                let reversed = py
+                    .import("builtins")
+                    .and_then(|builtins| builtins.getattr("reversed"))
+                    .and_then(|reversed| reversed.call1((self.container.bind(py),)))
+                    .and_then(|it| Ok(it.downcast_into::<PyIterator>()?.unbind()));
+                match reversed {
+                    Ok(it) => self.backward = Some(it),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            self.backward.as_ref()?
+        } else {
+            &self.forward
+        };
+
+        self.remaining -= 1;
+        iter.bind(py).clone().next()
+    }
+}
+
+impl Iterator for PyContainerIterator {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl DoubleEndedIterator for PyContainerIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.step(py, true).map(|x| x.map(Bound::unbind)))
+    }
+}
+
+impl PyIter for PyContainerIterator {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        self.step(py, false)
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.container)?;
+        visit.call(&self.forward)?;
+        visit.call(&self.backward)
+    }
+
+    // the iterators of dicts and sets can't be copied
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("a dict or set"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("a dict or set"))
+    }
+}
+
+impl ExactSizeIterator for PyContainerIterator {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
@@ -1,23 +1,39 @@
-use pyo3::prelude::*;
+use pyo3::{PyTraverseError, PyVisit, prelude::*, types::PyTuple};
 
-type PyDoubleEndedIteratorT =
-    Box<dyn DoubleEndedIterator<Item = PyResult<Py<PyAny>>> + Send + Sync>;
-#[pyo3::pyclass(generic)]
+use crate::py_iter::PyIter;
+
+pub type PyDoubleEndedIteratorT = Box<dyn crate::py_iter::PyDoubleEndedIter>;
+#[pyo3::pyclass(frozen, generic)]
 pub struct PyDoubleEndedIterator {
-    iter: PyDoubleEndedIteratorT,
+    iter: crate::exclusive::Exclusive<PyDoubleEndedIteratorT>,
 }
 
 impl PyDoubleEndedIterator {
     pub fn new(iter: PyDoubleEndedIteratorT) -> Self {
-        Self { iter }
+        Self {
+            iter: crate::exclusive::Exclusive::new(iter),
+        }
+    }
+}
+
+impl crate::exclusive::Guarded for PyDoubleEndedIterator {
+    type Inner = PyDoubleEndedIteratorT;
+
+    fn guarded(&self) -> &crate::exclusive::Exclusive<PyDoubleEndedIteratorT> {
+        &self.iter
     }
 }
 
 #[macros::register_methods(self_generic = S)]
 impl crate::double_ended_iterator::PyDoubleEndedIterator {
     #[macros::method_self_arg]
-    pub fn take_inner(&mut self) -> PyDoubleEndedIteratorT {
-        std::mem::replace(&mut self.iter, Box::new(std::iter::empty()))
+    pub fn take_inner(&self) -> PyResult<PyDoubleEndedIteratorT> {
+        Python::with_gil(|py| {
+            Ok(std::mem::replace(
+                &mut *self.iter.lock(py)?,
+                Box::new(std::iter::empty()),
+            ))
+        })
     }
 
     #[doc = "Creates a new iterator that traverses the elements
@@ -26,25 +42,117 @@ impl crate::double_ended_iterator::PyDoubleEndedIterator {
              Examples:
                  iter # [1, 2, 3]
                  iter.rev() # [3, 2, 1]"]
-    pub fn rev<S>(iter: S) -> std::iter::Rev<S>
+    pub fn rev<S>(iter: S) -> crate::py_iter::Rev<S>
     where
         S: DoubleEndedIterator<Item = PyResult<Py<PyAny>>>,
     {
-        iter.rev()
+        crate::py_iter::Rev(iter)
     }
 }
 
 #[macros::add_trait_methods(PyDoubleEndedIterator, (PyBaseIterator, exclude=(enumerate)))]
 #[pymethods]
 impl PyDoubleEndedIterator {
-    #[doc = "Consumes the first `n` elements of the iterator.
+    pub fn __iter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    pub fn __next__(slf: &Bound<'_, Self>) -> PyResult<Option<Py<PyAny>>> {
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(it.next_with(slf.py()).transpose()?.map(Bound::unbind))
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // required by pyo3
+    pub fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        // skipped while the iterator is in use, which only keeps its references alive longer
+        if let Some(iter) = self.iter.try_lock() {
+            iter.traverse(&visit)?;
+        }
+        Ok(())
+    }
+
+    pub fn __clear__(&self) {
+        let iter = self
+            .iter
+            .try_lock()
+            .map(|mut iter| std::mem::replace(&mut *iter, Box::new(std::iter::empty())));
+        drop(iter);
+    }
+
+    pub fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    pub fn __exit__(
+        slf: &Bound<'_, Self>,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        Self::close(slf)
+    }
+
+    #[doc = "Closes the iterator, along with the generators and other closable iterators
+             it's built on, leaving it empty. Called on leaving a `with` block over it.
              
              Examples:
-                 iter # [4, 9, 16]
-                 iter.take(2) # [4, 9]"]
-    pub fn take(&mut self, n: usize) -> Self {
-        Self::new(Box::new(
-            self.iter.by_ref().take(n).collect::<Vec<_>>().into_iter(),
-        ))
+                 iter # [1, 2, 3]
+                 iter.close()
+                 iter # []"]
+    pub fn close(slf: &Bound<'_, Self>) -> PyResult<()> {
+        slf.get().take_inner()?.close(slf.py())
+    }
+
+    #[doc = "Returns a copy of the iterator, which yields the remaining elements independently
+             of it and without buffering them as `tee` does. Raises `TypeError` naming the
+             stage that can't be copied, such as a generator source or `par_map`.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 copy = iter.clone()
+                 next(iter) # 1
+                 copy # [1, 2, 3]"]
+    pub fn clone(slf: &Bound<'_, Self>) -> PyResult<Self> {
+        crate::exclusive::exclusively(slf, |it| Ok(Self::new(it.try_clone(slf.py())?)))
+    }
+
+    pub fn __copy__(slf: &Bound<'_, Self>) -> PyResult<Self> {
+        Self::clone(slf)
+    }
+
+    #[doc = "Returns the position of the iterator as plain Python objects, from which
+             `from_state` rebuilds it, in another process too once pickled. Raises
+             `TypeError` naming the stage whose progress can't be saved, such as a
+             generator source or `par_map`.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 next(iter) # 1
+                 from_state(iter.state()) # [2, 3]"]
+    pub fn state(slf: &Bound<'_, Self>) -> PyResult<Py<PyAny>> {
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(crate::state::save(slf.py(), "double_ended", &*it)?.unbind())
+        })
+    }
+
+    pub fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyTuple>> {
+        crate::state::reduce(slf.py(), Self::state(slf)?.into_bound(slf.py()))
+    }
+
+    pub fn __length_hint__(slf: &Bound<'_, Self>) -> PyResult<usize> {
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(crate::base_iterator::length_hint(it.size_hint()))
+        })
+    }
+
+    #[doc = "Returns the bounds on the remaining length of the iterator, as a
+             `(lower, upper)` tuple where `upper` is `None` if unknown.
+             
+             Examples:
+                 iter # [1, 2, 3]
+                 iter.filter(lambda x: x > 1).size_hint() # (0, 3)"]
+    pub fn size_hint(slf: &Bound<'_, Self>) -> PyResult<(usize, Option<usize>)> {
+        crate::exclusive::exclusively(slf, |it| Ok(it.size_hint()))
     }
 }
@@ -0,0 +1,142 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+use pyo3::{PyClass, exceptions::PyValueError, prelude::*, pyclass::boolean_struct::True};
+
+thread_local! {
+    static MARKER: u8 = const { 0 };
+}
+
+/// Identifies the current thread more cheaply than `ThreadId`, and is never 0
+fn current_thread() -> usize {
+    MARKER.with(|marker| std::ptr::from_ref(marker) as usize)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Owner {
+    // the thread using the value, or 0 if none is
+    thread: usize,
+    waiting: usize,
+}
+
+/// A value used by one thread at a time, kept on the object it belongs to. Unlike a plain
+/// `Mutex`, threads wait for it detached from the interpreter, and a thread that already holds it
+/// gets `ValueError` rather than a deadlock.
+pub struct Exclusive<T> {
+    owner: Mutex<Owner>,
+    /// Notified whenever the value stops being used while threads are waiting
+    released: Condvar,
+    // only ever locked by the owner, or briefly by the garbage collector
+    value: Mutex<T>,
+}
+
+impl<T> Exclusive<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            owner: Mutex::new(Owner {
+                thread: 0,
+                waiting: 0,
+            }),
+            released: Condvar::new(),
+            value: Mutex::new(value),
+        }
+    }
+
+    /// Waits until no other thread is using the value, raising `ValueError` if this one is
+    pub fn lock(&self, py: Python<'_>) -> PyResult<Exclusively<'_, T>> {
+        let thread = current_thread();
+
+        let mut owner = lock(&self.owner);
+        if owner.thread == thread {
+            return Err(PyValueError::new_err("iterator already executing"));
+        }
+        if owner.thread == 0 {
+            owner.thread = thread;
+            drop(owner);
+        } else {
+            drop(owner);
+            let (owner, released) = (&self.owner, &self.released);
+            py.allow_threads(|| {
+                let mut owner = lock(owner);
+                owner.waiting += 1;
+                while owner.thread != 0 {
+                    owner = released.wait(owner).unwrap_or_else(PoisonError::into_inner);
+                }
+                owner.waiting -= 1;
+                owner.thread = thread;
+            });
+        }
+
+        Ok(Exclusively {
+            value: lock(&self.value),
+            _owned: Owned(self),
+        })
+    }
+
+    /// Returns the value if nothing is using it, for the garbage collector
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.value.try_lock().ok()
+    }
+}
+
+struct Owned<'a, T>(&'a Exclusive<T>);
+
+impl<T> Drop for Owned<'_, T> {
+    fn drop(&mut self) {
+        let mut owner = lock(&self.0.owner);
+        owner.thread = 0;
+        let notify = owner.waiting > 0;
+        drop(owner);
+
+        if notify {
+            self.0.released.notify_all();
+        }
+    }
+}
+
+/// Access to the value of an `Exclusive`, which other threads wait for until it's dropped
+pub struct Exclusively<'a, T> {
+    // dropped before the value is released to other threads
+    value: MutexGuard<'a, T>,
+    _owned: Owned<'a, T>,
+}
+
+impl<T> Deref for Exclusively<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Exclusively<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// An iterator class keeping its iterator in an `Exclusive`
+pub trait Guarded: PyClass<Frozen = True> + Sync {
+    type Inner;
+
+    fn guarded(&self) -> &Exclusive<Self::Inner>;
+}
+
+/// Runs `f` on the iterator behind `slf` once no other thread is using it, so that concurrent
+/// `__next__` calls are serialized rather than racing, with or without the GIL. Using an iterator
+/// from within one of its own callbacks raises `ValueError`, as advancing a running generator
+/// does.
+pub fn exclusively<T, R>(
+    slf: &Bound<'_, T>,
+    f: impl FnOnce(&mut T::Inner) -> PyResult<R>,
+) -> PyResult<R>
+where
+    T: Guarded,
+{
+    f(&mut *slf.get().guarded().lock(slf.py())?)
+}
@@ -0,0 +1,305 @@
+use pyo3::{
+    IntoPyObjectExt, PyTraverseError, PyVisit,
+    prelude::*,
+    types::{PyFunction, PyTuple},
+};
+
+use crate::{
+    any_iterator::{AnyIterator, WithLength},
+    base_iterator::PyBaseIteratorT,
+    interleaving::{Interleave, KMerge},
+    iterators::{PyBaseIterator, PyExactSizeIterator},
+    pipeline::{Pipeline, Stage},
+    py_iter::PyIter,
+};
+
+#[pyo3::pyclass(frozen, generic)]
+pub struct PyInfiniteIterator {
+    iter: crate::exclusive::Exclusive<PyBaseIteratorT>,
+}
+
+impl PyInfiniteIterator {
+    pub fn new(iter: PyBaseIteratorT) -> Self {
+        Self {
+            iter: crate::exclusive::Exclusive::new(iter),
+        }
+    }
+}
+
+impl crate::exclusive::Guarded for PyInfiniteIterator {
+    type Inner = PyBaseIteratorT;
+
+    fn guarded(&self) -> &crate::exclusive::Exclusive<PyBaseIteratorT> {
+        &self.iter
+    }
+}
+
+#[macros::register_methods(self_generic = S)]
+impl crate::infinite_iterator::PyInfiniteIterator {
+    #[macros::method_self_arg]
+    pub fn take_inner(&self) -> PyResult<PyBaseIteratorT> {
+        Python::with_gil(|py| {
+            Ok(std::mem::replace(
+                &mut *self.iter.lock(py)?,
+                Box::new(std::iter::empty()),
+            ))
+        })
+    }
+}
+
+// Every method that would have to exhaust the iterator raises instead of hanging, leaving the
+// iterator untouched.
+#[macros::add_trait_methods(
+    PyInfiniteIterator,
+    (
+        PyBaseIterator,
+        unbounded=(
+            to_list,
+            to_tuple,
+            to_set,
+            to_frozenset,
+            to_dict,
+            to_deque,
+            join,
+            to_bytes,
+            collect,
+            fold,
+            count,
+            collect_errors,
+            partition,
+            unzip,
+            combinations,
+            combinations_with_replacement,
+            permutations,
+            powerset
+        ),
+        exclude=(cycle, interleave, interleave_shortest, merge, kmerge, tee, take)
+    )
+)]
+#[pymethods]
+#[allow(clippy::unused_self)]
+impl PyInfiniteIterator {
+    pub fn __iter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    pub fn __next__(slf: &Bound<'_, Self>) -> PyResult<Option<Py<PyAny>>> {
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(it.next_with(slf.py()).transpose()?.map(Bound::unbind))
+        })
+    }
+
+    #[allow(clippy::needless_pass_by_value)] // required by pyo3
+    pub fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        // skipped while the iterator is in use, which only keeps its references alive longer
+        if let Some(iter) = self.iter.try_lock() {
+            iter.traverse(&visit)?;
+        }
+        Ok(())
+    }
+
+    pub fn __clear__(&self) {
+        let iter = self
+            .iter
+            .try_lock()
+            .map(|mut iter| std::mem::replace(&mut *iter, Box::new(std::iter::empty())));
+        drop(iter);
+    }
+
+    pub fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    pub fn __exit__(
+        slf: &Bound<'_, Self>,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        Self::close(slf)
+    }
+
+    #[doc = "Closes the iterator, along with the generators and other closable iterators
+             it's built on, leaving it empty. Called on leaving a `with` block over it.
+             
+             Examples:
+                 iter # [1, 2, 3, ...]
+                 iter.close()
+                 iter # []"]
+    pub fn close(slf: &Bound<'_, Self>) -> PyResult<()> {
+        slf.get().take_inner()?.close(slf.py())
+    }
+
+    #[doc = "Returns a copy of the iterator, which yields the remaining elements independently
+             of it and without buffering them as `tee` does. Raises `TypeError` naming the
+             stage that can't be copied, such as a generator source or `par_map`.
+             
+             Examples:
+                 iter # [1, 2, 3, ...]
+                 copy = iter.clone()
+                 next(iter) # 1
+                 copy # [1, 2, 3, ...]"]
+    pub fn clone(slf: &Bound<'_, Self>) -> PyResult<Self> {
+        crate::exclusive::exclusively(slf, |it| Ok(Self::new(it.try_clone(slf.py())?)))
+    }
+
+    pub fn __copy__(slf: &Bound<'_, Self>) -> PyResult<Self> {
+        Self::clone(slf)
+    }
+
+    #[doc = "Returns the position of the iterator as plain Python objects, from which
+             `from_state` rebuilds it, in another process too once pickled. Raises
+             `TypeError` naming the stage whose progress can't be saved, such as a
+             generator source or `par_map`.
+             
+             Examples:
+                 iter # [1, 2, 3, ...]
+                 next(iter) # 1
+                 from_state(iter.state()) # [2, 3, ...]"]
+    pub fn state(slf: &Bound<'_, Self>) -> PyResult<Py<PyAny>> {
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(crate::state::save(slf.py(), "infinite", &*it)?.unbind())
+        })
+    }
+
+    pub fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyTuple>> {
+        crate::state::reduce(slf.py(), Self::state(slf)?.into_bound(slf.py()))
+    }
+
+    pub fn __length_hint__(slf: &Bound<'_, Self>) -> PyResult<usize> {
+        crate::exclusive::exclusively(slf, |it| {
+            Ok(crate::base_iterator::length_hint(it.size_hint()))
+        })
+    }
+
+    #[doc = "Returns the bounds on the remaining length of the iterator, as a
+             `(lower, upper)` tuple where `upper` is `None` if unknown.
+             
+             Examples:
+                 iter # [1, 2, 3, ...]
+                 iter.filter(lambda x: x > 1).size_hint() # (0, None)"]
+    pub fn size_hint(slf: &Bound<'_, Self>) -> PyResult<(usize, Option<usize>)> {
+        crate::exclusive::exclusively(slf, |it| Ok(it.size_hint()))
+    }
+
+    #[doc = "Creates a new iterator over the first `n` elements of the iterator.
+             Its length is known unless the iterator may still run out, as a cycle
+             over an empty iterator does.
+             
+             Examples:
+                 iter # [4, 9, 16, ...]
+                 iter.take(2) # [4, 9]"]
+    pub fn take(slf: &Bound<'_, Self>, n: usize) -> PyResult<Py<PyAny>> {
+        let taken = Pipeline::push(slf.get().take_inner()?, Stage::Take(n));
+        match taken.size_hint() {
+            (lower, Some(upper)) if lower == upper => {
+                PyExactSizeIterator::new(Box::new(WithLength::new(taken, lower)))
+                    .into_py_any(slf.py())
+            }
+            _ => PyBaseIterator::new(Box::new(taken)).into_py_any(slf.py()),
+        }
+    }
+
+    #[doc = "Returns the iterator itself, as it already repeats forever."]
+    pub fn cycle(&self) -> PyResult<Self> {
+        Ok(Self::new(self.take_inner()?))
+    }
+
+    #[doc = "Creates a new iterator alternating between elements of this iterator
+             and `other`, continuing with this one once `other` runs out.
+             
+             Examples:
+                 iter # [1, 2, 3, ...]
+                 iter.interleave([4, 5]) # [1, 4, 2, 5, 3, 4, ...]"]
+    pub fn interleave(slf: &Bound<'_, Self>, other: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let other = AnyIterator::from_iterable(other)?.into_base();
+        Ok(Self::new(Box::new(Interleave::new(
+            slf.get().take_inner()?,
+            other,
+            false,
+        ))))
+    }
+
+    #[doc = "Creates a new iterator alternating between elements of this iterator
+             and `other`, stopping as soon as `other` runs out, which it only does
+             if it's finite.
+             
+             Examples:
+                 iter # [1, 2, 3, ...]
+                 iter.interleave_shortest([4, 5]) # [1, 4, 2, 5, 3]"]
+    pub fn interleave_shortest(
+        slf: &Bound<'_, Self>,
+        other: &Bound<'_, PyAny>,
+    ) -> PyResult<Py<PyAny>> {
+        let other = AnyIterator::from_iterable(other)?;
+        let infinite = other.is_infinite();
+        let interleaved = Box::new(Interleave::new(
+            slf.get().take_inner()?,
+            other.into_base(),
+            true,
+        ));
+        if infinite {
+            Self::new(interleaved).into_py_any(slf.py())
+        } else {
+            PyBaseIterator::new(interleaved).into_py_any(slf.py())
+        }
+    }
+
+    #[pyo3(signature = (other, key = None))]
+    #[doc = "Creates a new iterator merging this iterator and `other`, both assumed
+             to be sorted, into a single sorted iterator. Elements are compared by
+             `key` if given, and ties are yielded from this iterator first.
+             
+             Examples:
+                 iter # [1, 3, 5, ...]
+                 iter.merge([2, 3, 4]) # [1, 2, 3, 3, 4, 5, ...]"]
+    pub fn merge(
+        slf: &Bound<'_, Self>,
+        other: &Bound<'_, PyAny>,
+        key: Option<Py<PyFunction>>,
+    ) -> PyResult<Self> {
+        let other = AnyIterator::from_iterable(other)?.into_base();
+        Ok(Self::new(Box::new(KMerge::new(
+            slf.get().take_inner()?,
+            vec![other],
+            key,
+        ))))
+    }
+
+    #[pyo3(signature = (*others, key = None))]
+    #[doc = "Creates a new iterator merging this iterator and every one of `others`,
+             all assumed to be sorted, into a single sorted iterator. Elements are
+             compared by `key` if given, and ties are yielded in argument order.
+             
+             Examples:
+                 iter # [1, 4, 7, ...]
+                 iter.kmerge([2, 5], [3, 6]) # [1, 2, 3, 4, 5, 6, 7, ...]"]
+    pub fn kmerge(
+        slf: &Bound<'_, Self>,
+        others: &Bound<'_, PyTuple>,
+        key: Option<Py<PyFunction>>,
+    ) -> PyResult<Self> {
+        let others = others
+            .iter()
+            .map(|other| AnyIterator::from_iterable(&other).map(AnyIterator::into_base))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Self::new(Box::new(KMerge::new(
+            slf.get().take_inner()?,
+            others,
+            key,
+        ))))
+    }
+
+    #[doc = "Splits the iterator into `n` independent iterators, each of them
+             infinite too. Elements are pulled from the original iterator lazily
+             and buffered until every branch has yielded them.
+             
+             Examples:
+                 iter # [1, 2, 3, ...]
+                 a, b = iter.tee(2)
+                 a.map(lambda x: x * 2) # [2, 4, 6, ...]
+                 b # [1, 2, 3, ...]"]
+    pub fn tee(slf: &Bound<'_, Self>, n: usize) -> PyResult<Py<PyTuple>> {
+        crate::tee_iterator::branches(slf.py(), slf.get().take_inner()?, n, Self::new)
+    }
+}
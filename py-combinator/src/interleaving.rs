@@ -0,0 +1,604 @@
+use pyo3::{IntoPyObjectExt, PyTraverseError, PyVisit, prelude::*, types::PyFunction};
+
+use crate::{
+    any_iterator::AnyIterator,
+    arguments::Iterable,
+    base_iterator::PyBaseIteratorT,
+    iterators::{PyBaseIterator, PyExactSizeIterator, PyInfiniteIterator},
+    notes,
+    py_iter::{self, PyExactSizeIter, PyIter},
+    state,
+};
+
+fn checked_add_hints(
+    (a_lower, a_upper): (usize, Option<usize>),
+    (b_lower, b_upper): (usize, Option<usize>),
+) -> (usize, Option<usize>) {
+    (
+        a_lower.saturating_add(b_lower),
+        a_upper.zip(b_upper).and_then(|(a, b)| a.checked_add(b)),
+    )
+}
+
+pub struct Interleave<A, B> {
+    // each side is dropped once it runs out
+    a: Option<A>,
+    b: Option<B>,
+    a_next: bool,
+    shortest: bool,
+}
+
+impl<A, B> Interleave<A, B> {
+    pub fn new(a: A, b: B, shortest: bool) -> Self {
+        Interleave {
+            a: Some(a),
+            b: Some(b),
+            a_next: true,
+            shortest,
+        }
+    }
+}
+
+impl<A, B> Iterator for Interleave<A, B>
+where
+    A: Iterator<Item = PyResult<Py<PyAny>>>,
+    B: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for _ in 0..2 {
+            let a_turn = self.a_next;
+            self.a_next = !self.a_next;
+
+            let item = if a_turn {
+                self.a.as_mut().and_then(Iterator::next)
+            } else {
+                self.b.as_mut().and_then(Iterator::next)
+            };
+
+            if item.is_some() {
+                return item;
+            }
+
+            if self.shortest || a_turn {
+                self.a = None;
+            }
+            if self.shortest || !a_turn {
+                self.b = None;
+            }
+            if self.shortest {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let a = self.a.as_ref().map_or((0, Some(0)), Iterator::size_hint);
+        let b = self.b.as_ref().map_or((0, Some(0)), Iterator::size_hint);
+
+        if !self.shortest {
+            return checked_add_hints(a, b);
+        }
+
+        if self.a.is_none() || self.b.is_none() {
+            return (0, Some(0));
+        }
+
+        // alternation stops as soon as the side whose turn it is runs out
+        let ((first_lower, first_upper), (second_lower, second_upper)) =
+            if self.a_next { (a, b) } else { (b, a) };
+
+        let shortest_len = |first: usize, second: usize| {
+            if first > second {
+                second.checked_mul(2).and_then(|n| n.checked_add(1))
+            } else {
+                first.checked_mul(2)
+            }
+        };
+
+        let lower = shortest_len(first_lower, second_lower).unwrap_or(usize::MAX);
+        let upper = match (first_upper, second_upper) {
+            (Some(first), Some(second)) => shortest_len(first, second),
+            (Some(first), None) => first.checked_mul(2),
+            (None, Some(second)) => second.checked_mul(2).and_then(|n| n.checked_add(1)),
+            (None, None) => None,
+        };
+
+        (lower, upper)
+    }
+}
+
+impl<A, B> ExactSizeIterator for Interleave<A, B>
+where
+    A: ExactSizeIterator<Item = PyResult<Py<PyAny>>>,
+    B: ExactSizeIterator<Item = PyResult<Py<PyAny>>>,
+{
+}
+
+impl<A, B> PyIter for Interleave<A, B>
+where
+    A: PyIter,
+    B: PyIter,
+{
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        if let Some(a) = &self.a {
+            a.traverse(visit)?;
+        }
+        if let Some(b) = &self.b {
+            b.traverse(visit)?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        // both sides are closed even if the first raises
+        let a = self.a.as_mut().map_or(Ok(()), |a| a.close(py));
+        let b = self.b.as_mut().map_or(Ok(()), |b| b.close(py));
+        a.and(b)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        let a = self.a.as_ref().map_or(0, |a| a.stage_count(name));
+        let b = self.b.as_ref().map_or(0, |b| b.stage_count(name));
+        a + b
+    }
+
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("interleave"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("interleave"))
+    }
+}
+
+pub enum Separator {
+    Value(Py<PyAny>),
+    With(Py<PyFunction>),
+}
+
+impl Separator {
+    fn name(&self) -> &'static str {
+        match self {
+            Separator::Value(_) => "intersperse",
+            Separator::With(_) => "intersperse_with",
+        }
+    }
+}
+
+pub struct Intersperse<S> {
+    iter: S,
+    separator: Separator,
+    // the name notes give the stage, numbered among those interspersing the same way
+    name: String,
+    pending: Option<PyResult<Py<PyAny>>>,
+    started: bool,
+    // the number of elements pulled from `iter` so far
+    seen: usize,
+}
+
+impl<S> Intersperse<S>
+where
+    S: PyIter,
+{
+    pub fn new(iter: S, separator: Separator) -> Self {
+        Intersperse {
+            name: notes::numbered(separator.name(), iter.stage_count(separator.name())),
+            iter,
+            separator,
+            pending: None,
+            started: false,
+            seen: 0,
+        }
+    }
+}
+
+impl<S> Iterator for Intersperse<S>
+where
+    S: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(x) = self.pending.take() {
+            return Some(x);
+        }
+
+        let x = self.iter.next()?;
+        self.seen += 1;
+        if !self.started {
+            self.started = true;
+            return Some(x);
+        }
+
+        // a failed separator is noted with the element it goes before
+        let separator = Python::with_gil(|py| match &self.separator {
+            Separator::Value(sep) => Ok(sep.clone_ref(py)),
+            Separator::With(f) => f.call0(py).map_err(|e| match &x {
+                Ok(x) => notes::add_note(py, e, &self.name, self.seen - 1, x.bind(py)),
+                Err(_) => e,
+            }),
+        });
+        self.pending = Some(x);
+        Some(separator)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let pending = usize::from(self.pending.is_some());
+
+        // every remaining element is preceded by a separator, except the very first one
+        let interspersed = |n: usize| {
+            n.checked_mul(2).and_then(|n| {
+                if self.started {
+                    n.checked_add(pending)
+                } else {
+                    Some(n.saturating_sub(1))
+                }
+            })
+        };
+
+        (
+            interspersed(lower).unwrap_or(usize::MAX),
+            upper.and_then(interspersed),
+        )
+    }
+}
+
+impl<S> ExactSizeIterator for Intersperse<S> where S: ExactSizeIterator<Item = PyResult<Py<PyAny>>> {}
+
+impl<S> PyIter for Intersperse<S>
+where
+    S: PyIter,
+{
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.iter.traverse(visit)?;
+        match &self.separator {
+            Separator::Value(sep) => visit.call(sep)?,
+            Separator::With(f) => visit.call(f)?,
+        }
+        visit.call(self.pending.as_ref().and_then(|x| x.as_ref().ok()))
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.iter.close(py)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        usize::from(self.separator.name() == name) + self.iter.stage_count(name)
+    }
+
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("intersperse"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("intersperse"))
+    }
+}
+
+struct HeapEntry {
+    key: Py<PyAny>,
+    value: Py<PyAny>,
+    source: usize,
+}
+
+impl HeapEntry {
+    // ties go to the earlier source so that merging is stable
+    fn less(&self, other: &HeapEntry, py: Python<'_>) -> PyResult<bool> {
+        let (a, b) = (self.key.bind(py), other.key.bind(py));
+        if a.lt(b)? {
+            Ok(true)
+        } else if self.source < other.source {
+            Ok(!b.lt(a)?)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+pub struct KMerge<S, T> {
+    first: S,
+    others: Vec<T>,
+    key: Option<Py<PyFunction>>,
+    // the name notes give the stage, numbered among the merges it's built on
+    name: String,
+    // the number of elements pulled from the sources so far
+    seen: usize,
+    heap: Vec<HeapEntry>,
+    // sources whose head isn't in the heap yet
+    pending: Vec<usize>,
+    done: bool,
+}
+
+impl<S, T> KMerge<S, T>
+where
+    S: PyIter,
+    T: PyIter,
+{
+    pub fn new(first: S, others: Vec<T>, key: Option<Py<PyFunction>>) -> Self {
+        let before = first.stage_count("kmerge")
+            + others
+                .iter()
+                .map(|other| other.stage_count("kmerge"))
+                .sum::<usize>();
+        KMerge {
+            first,
+            pending: (0..=others.len()).rev().collect(),
+            others,
+            key,
+            name: notes::numbered("kmerge", before),
+            seen: 0,
+            heap: Vec::new(),
+            done: false,
+        }
+    }
+}
+
+impl<S, T> KMerge<S, T>
+where
+    S: Iterator<Item = PyResult<Py<PyAny>>>,
+    T: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    fn source(&mut self, i: usize) -> &mut dyn Iterator<Item = PyResult<Py<PyAny>>> {
+        if i == 0 {
+            &mut self.first
+        } else {
+            &mut self.others[i - 1]
+        }
+    }
+
+    fn push(&mut self, py: Python<'_>, entry: HeapEntry) -> PyResult<()> {
+        self.heap.push(entry);
+
+        let mut i = self.heap.len() - 1;
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if !self.heap[i].less(&self.heap[parent], py)? {
+                break;
+            }
+            self.heap.swap(i, parent);
+            i = parent;
+        }
+
+        Ok(())
+    }
+
+    fn pop(&mut self, py: Python<'_>) -> PyResult<Option<HeapEntry>> {
+        if self.heap.is_empty() {
+            return Ok(None);
+        }
+
+        let top = self.heap.swap_remove(0);
+
+        let mut i = 0;
+        loop {
+            let mut smallest = i;
+            for child in [2 * i + 1, 2 * i + 2] {
+                if child < self.heap.len() && self.heap[child].less(&self.heap[smallest], py)? {
+                    smallest = child;
+                }
+            }
+            if smallest == i {
+                break;
+            }
+            self.heap.swap(i, smallest);
+            i = smallest;
+        }
+
+        Ok(Some(top))
+    }
+
+    fn refill(&mut self, py: Python<'_>) -> PyResult<()> {
+        while let Some(source) = self.pending.pop() {
+            let value = match self.source(source).next() {
+                Some(Ok(value)) => value,
+                Some(Err(e)) => {
+                    self.pending.push(source);
+                    return Err(e);
+                }
+                None => continue,
+            };
+
+            let key = match &self.key {
+                Some(f) => f
+                    .call1(py, (value.bind(py),))
+                    .map_err(|e| notes::add_note(py, e, &self.name, self.seen, value.bind(py))),
+                None => Ok(value.clone_ref(py)),
+            };
+            self.seen += 1;
+
+            // a failed key or comparison leaves the heap in no particular order
+            if let Err(e) = key.and_then(|key| self.push(py, HeapEntry { key, value, source })) {
+                self.done = true;
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S, T> Iterator for KMerge<S, T>
+where
+    S: Iterator<Item = PyResult<Py<PyAny>>>,
+    T: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        Python::with_gil(|py| {
+            if let Err(e) = self.refill(py) {
+                return Some(Err(e));
+            }
+
+            match self.pop(py) {
+                Ok(Some(entry)) => {
+                    self.pending.push(entry.source);
+                    Some(Ok(entry.value))
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    self.done = true;
+                    Some(Err(e))
+                }
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+
+        let heap = (self.heap.len(), Some(self.heap.len()));
+        self.others.iter().map(Iterator::size_hint).fold(
+            checked_add_hints(heap, self.first.size_hint()),
+            checked_add_hints,
+        )
+    }
+}
+
+impl<S, T> ExactSizeIterator for KMerge<S, T>
+where
+    S: ExactSizeIterator<Item = PyResult<Py<PyAny>>>,
+    T: ExactSizeIterator<Item = PyResult<Py<PyAny>>>,
+{
+}
+
+impl<S, T> PyIter for KMerge<S, T>
+where
+    S: PyIter,
+    T: PyIter,
+{
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.first.traverse(visit)?;
+        for other in &self.others {
+            other.traverse(visit)?;
+        }
+        visit.call(&self.key)?;
+        for entry in &self.heap {
+            visit.call(&entry.key)?;
+            visit.call(&entry.value)?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        // every source is closed even if an earlier one raises, which is the error passed on
+        let first = self.first.close(py);
+        self.others
+            .iter_mut()
+            .map(|other| other.close(py))
+            .fold(first, PyResult::and)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        let own = usize::from(name == "kmerge");
+        self.others
+            .iter()
+            .map(|other| other.stage_count(name))
+            .fold(own + self.first.stage_count(name), |a, b| a + b)
+    }
+
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("kmerge"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("kmerge"))
+    }
+}
+
+/// Wraps an interleaving or merge in the base class, or in the infinite one if one of its inputs
+/// never runs out, so that exhausting it raises instead of hanging
+fn unbounded_or_base(py: Python<'_>, iter: PyBaseIteratorT, infinite: bool) -> PyResult<Py<PyAny>> {
+    if infinite {
+        PyInfiniteIterator::new(iter).into_py_any(py)
+    } else {
+        PyBaseIterator::new(iter).into_py_any(py)
+    }
+}
+
+/// Interleaves an iterator with `other`, going on forever if `other` does, unless `shortest` is set
+pub fn interleave<S>(iter: S, other: &Bound<'_, PyAny>, shortest: bool) -> PyResult<Py<PyAny>>
+where
+    S: PyIter,
+{
+    let py = other.py();
+    let other = AnyIterator::from_iterable(other)?;
+    let infinite = !shortest && other.is_infinite();
+    let interleaved = Interleave::new(iter, other.into_base(), shortest);
+    unbounded_or_base(py, Box::new(interleaved), infinite)
+}
+
+/// Merges an iterator with `others`, going on forever if one of them does
+pub fn kmerge<S>(
+    py: Python<'_>,
+    iter: S,
+    others: &[Iterable],
+    key: Option<Py<PyFunction>>,
+) -> PyResult<Py<PyAny>>
+where
+    S: PyIter,
+{
+    let others = others
+        .iter()
+        .map(|other| AnyIterator::from_iterable(other.0.bind(py)))
+        .collect::<PyResult<Vec<_>>>()?;
+    let infinite = others.iter().any(AnyIterator::is_infinite);
+    let others = others.into_iter().map(AnyIterator::into_base).collect();
+    unbounded_or_base(py, Box::new(KMerge::new(iter, others, key)), infinite)
+}
+
+/// Interleaves a sized iterator with `other`, keeping the length known if `other` has one
+pub fn interleave_sized<S>(iter: S, other: &Bound<'_, PyAny>, shortest: bool) -> PyResult<Py<PyAny>>
+where
+    S: PyExactSizeIter,
+{
+    let py = other.py();
+    match AnyIterator::from_iterable(other)?.into_exact_size() {
+        Ok(other) => PyExactSizeIterator::new(Box::new(Interleave::new(iter, other, shortest)))
+            .into_py_any(py),
+        Err(other) => {
+            let infinite = !shortest && other.is_infinite();
+            let interleaved = Interleave::new(iter, other.into_base(), shortest);
+            unbounded_or_base(py, Box::new(interleaved), infinite)
+        }
+    }
+}
+
+/// Merges a sized iterator with `others`, keeping the length known if all of them have one
+pub fn kmerge_sized<S>(
+    py: Python<'_>,
+    iter: S,
+    others: &[Iterable],
+    key: Option<Py<PyFunction>>,
+) -> PyResult<Py<PyAny>>
+where
+    S: PyExactSizeIter,
+{
+    let others = others
+        .iter()
+        .map(|other| AnyIterator::from_iterable(other.0.bind(py)))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    if others.iter().all(AnyIterator::is_exact_size) {
+        let others = others
+            .into_iter()
+            .filter_map(|other| other.into_exact_size().ok())
+            .collect();
+        PyExactSizeIterator::new(Box::new(KMerge::new(iter, others, key))).into_py_any(py)
+    } else {
+        let infinite = others.iter().any(AnyIterator::is_infinite);
+        let others = others.into_iter().map(AnyIterator::into_base).collect();
+        unbounded_or_base(py, Box::new(KMerge::new(iter, others, key)), infinite)
+    }
+}
@@ -1,6 +1,10 @@
-use pyo3::{prelude::*, types::PyIterator};
+use pyo3::{PyTraverseError, PyVisit, intern, prelude::*, types::PyIterator};
+
+use crate::{
+    py_iter::{self, PyIter},
+    state,
+};
 
-#[pyclass]
 pub struct PyIterIterator {
     iter: Py<PyIterator>,
 }
@@ -17,12 +21,47 @@ impl Iterator for PyIterIterator {
     type Item = PyResult<Py<PyAny>>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // `operator.length_hint` is only an estimate, so it can't bound the length from above
         Python::with_gil(|py| {
-            self.iter
-                .clone_ref(py)
-                .into_bound(py)
-                .next()
-                .map(|x| x.map(pyo3::Bound::unbind))
+            let hint = py
+                .import("operator")
+                .and_then(|operator| operator.getattr("length_hint"))
+                .and_then(|length_hint| length_hint.call1((self.iter.bind(py), 0)))
+                .and_then(|hint| hint.extract::<usize>());
+            (hint.unwrap_or(0), None)
         })
     }
 }
+
+impl PyIter for PyIterIterator {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        self.iter.bind(py).clone().next()
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.iter)
+    }
+
+    // generators and other iterators with a `close` method are closed, as a `for` loop over them
+    // isn't, so that a partly consumed one doesn't wait on the collector to run its cleanup
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        let iter = self.iter.bind(py);
+        if iter.hasattr(intern!(py, "close"))? {
+            iter.call_method0(intern!(py, "close"))?;
+        }
+        Ok(())
+    }
+
+    // copying a Python iterator would mean buffering it, as `tee` does
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("a Python iterator"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("a Python iterator"))
+    }
+}
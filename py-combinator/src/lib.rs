@@ -1,53 +1,91 @@
 #![warn(clippy::pedantic)]
 
+mod amap;
+mod any_iterator;
+mod arguments;
+mod async_iterator;
 mod base_iterator;
+mod blueprint;
+mod combinatorics;
+mod container_iterator;
 mod double_ended_iterator;
 mod exact_size_iterator;
+mod exclusive;
+mod infinite_iterator;
+mod interleaving;
 mod iter_iterator;
-mod list_iterator;
+mod notes;
+mod parallel;
+mod pipeline;
+mod py_iter;
+mod range_iterator;
+mod sequence_iterator;
 mod sized_double_ended_iterator;
+mod sources;
+mod state;
+mod tee_iterator;
 
 mod iterators {
     #[allow(unused_imports)]
     pub use crate::{
         base_iterator::PyBaseIterator, double_ended_iterator::PyDoubleEndedIterator,
-        exact_size_iterator::PyExactSizeIterator,
+        exact_size_iterator::PyExactSizeIterator, infinite_iterator::PyInfiniteIterator,
         sized_double_ended_iterator::PySizedDoubleEndedIterator,
     };
 }
 
-use pyo3::{IntoPyObjectExt, exceptions::PyTypeError, prelude::*, types::PyList};
+use pyo3::prelude::*;
 
+/// Creates the most capable iterator over `iterable`. Mutable containers such as lists, dicts
+/// and sets raise `RuntimeError` if they change size during iteration, unless `snapshot` is
+/// set, in which case they're copied up front.
+///
+/// `kind` asks for a specific iterator class instead, dropping the capabilities it doesn't
+/// need, and raises `TypeError` if `iterable` can't provide the ones it does. `length_hint`
+/// supplies the length of iterables that don't know it, such as generators.
 #[pyfunction]
-fn iterator_from(iterable: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
-    Python::with_gil(|py| {
-        if let Ok(list) = iterable.downcast::<PyList>() {
-            let list_iter = list_iterator::PyListIterator::new(list);
-            sized_double_ended_iterator::PySizedDoubleEndedIterator::new(Box::new(list_iter))
-                .into_py_any(py)
-        } else {
-            match iterable.try_iter() {
-                Ok(it) => base_iterator::PyBaseIterator::new(Box::new(
-                    iter_iterator::PyIterIterator::new(&it),
-                ))
-                .into_py_any(py),
-                Err(e) => Err(PyTypeError::new_err(format!(
-                    "Cannot construct iterator from type {} ({})",
-                    e,
-                    iterable.get_type().name()?,
-                ))),
-            }
-        }
-    })
+#[pyo3(signature = (iterable, *, snapshot = false, kind = "auto", length_hint = None))]
+fn iterator_from(
+    iterable: &Bound<'_, PyAny>,
+    snapshot: bool,
+    kind: &str,
+    length_hint: Option<usize>,
+) -> PyResult<Py<PyAny>> {
+    let kind = any_iterator::Kind::parse(kind)?;
+
+    // iterator classes are consumed when converted, so they're checked beforehand
+    if let Some((exact_size, double_ended)) =
+        any_iterator::AnyIterator::class_capabilities(iterable)
+    {
+        kind.check(exact_size || length_hint.is_some(), double_ended, iterable)?;
+    }
+
+    let mut it = any_iterator::AnyIterator::from_iterable_with(iterable, snapshot)?;
+    if let Some(length) = length_hint {
+        it = it.with_length(length)?;
+    }
+
+    it.into_kind(kind, iterable)?.into_py_any(iterable.py())
 }
 
-#[pymodule]
+#[pymodule(gil_used = false)]
 #[allow(clippy::unnecessary_wraps)]
 fn _py_combinator(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<base_iterator::PyBaseIterator>()?;
     m.add_class::<exact_size_iterator::PyExactSizeIterator>()?;
     m.add_class::<double_ended_iterator::PyDoubleEndedIterator>()?;
     m.add_class::<sized_double_ended_iterator::PySizedDoubleEndedIterator>()?;
+    m.add_class::<infinite_iterator::PyInfiniteIterator>()?;
+    m.add_class::<async_iterator::PyAsyncIterator>()?;
+    m.add_class::<blueprint::PyPipeline>()?;
     let _ = m.add_function(wrap_pyfunction!(iterator_from, m)?);
+    let _ = m.add_function(wrap_pyfunction!(async_iterator::async_iterator_from, m)?);
+    let _ = m.add_function(wrap_pyfunction!(sources::range, m)?);
+    let _ = m.add_function(wrap_pyfunction!(sources::count, m)?);
+    let _ = m.add_function(wrap_pyfunction!(sources::repeat, m)?);
+    let _ = m.add_function(wrap_pyfunction!(sources::repeat_with, m)?);
+    let _ = m.add_function(wrap_pyfunction!(sources::iterate, m)?);
+    let _ = m.add_function(wrap_pyfunction!(sources::from_fn, m)?);
+    let _ = m.add_function(wrap_pyfunction!(state::from_state, m)?);
     Ok(())
 }
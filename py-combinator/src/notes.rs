@@ -0,0 +1,34 @@
+use pyo3::{intern, prelude::*};
+
+/// The longest `repr` of an element quoted in a note, in characters
+const REPR_LIMIT: usize = 80;
+
+/// Names a stage by its kind and its number among the stages of that kind, given how many come
+/// `before` it
+pub fn numbered(stage: &str, before: usize) -> String {
+    format!("{stage} #{}", before + 1)
+}
+
+/// Adds a note to `e` (PEP 678) saying which `stage` raised it and on which element, given as
+/// `x` and its `index` among the elements the stage was given
+pub fn add_note(
+    py: Python<'_>,
+    e: PyErr,
+    stage: &str,
+    index: usize,
+    x: &Bound<'_, PyAny>,
+) -> PyErr {
+    let repr = x.repr().map_or_else(
+        |_| "<unrepresentable object>".to_owned(),
+        |repr| repr.to_string(),
+    );
+    let repr = match repr.char_indices().nth(REPR_LIMIT) {
+        Some((end, _)) => format!("{}...", &repr[..end]),
+        None => repr,
+    };
+
+    let note = format!("in {stage}, on element {index}: {repr}");
+    // an exception object whose `add_note` fails is still worth raising without the note
+    let _ = e.value(py).call_method1(intern!(py, "add_note"), (note,));
+    e
+}
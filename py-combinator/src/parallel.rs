@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+};
+use std::thread;
+
+use pyo3::{PyTraverseError, PyVisit, exceptions::PyValueError, prelude::*, types::PyFunction};
+
+use crate::{
+    notes,
+    py_iter::{self, PyIter},
+    state,
+};
+
+type Chunk = Vec<(usize, Py<PyAny>)>;
+
+/// Threads mapping the elements of one `par_map`, kept from its first element until it's
+/// dropped, which joins them. Each takes the GIL only for the duration of a call.
+struct Pool {
+    // taken when the pool is dropped, which ends the workers' loops
+    jobs: Option<mpsc::Sender<Chunk>>,
+    // only ever borrowed mutably, which keeps the iterator `Sync`
+    results: Mutex<mpsc::Receiver<(usize, PyResult<Py<PyAny>>)>>,
+    // set once the iterator is gone, so that chunks still queued are dropped unmapped
+    cancelled: Arc<AtomicBool>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl Pool {
+    fn new(f: &Arc<Py<PyFunction>>, name: &str, workers: usize) -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<Chunk>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let (result_sender, results) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let mut handles = Vec::with_capacity(workers);
+        for _ in 0..workers {
+            let job_receiver = Arc::clone(&job_receiver);
+            let result_sender = result_sender.clone();
+            let cancelled = Arc::clone(&cancelled);
+            let f = Arc::clone(f);
+            let name = name.to_owned();
+            handles.push(thread::spawn(move || {
+                // the lock is only held while waiting for a chunk, and the loop ends once the
+                // pool is dropped
+                while let Ok(chunk) = job_receiver
+                    .lock()
+                    .map_err(drop)
+                    .and_then(|jobs| jobs.recv().map_err(drop))
+                {
+                    for (i, x) in chunk {
+                        // checked with the GIL held, as the pool is only dropped with it, after
+                        // which `f` may have been cleared by the garbage collector
+                        let result = Python::with_gil(|py| {
+                            if cancelled.load(Ordering::Relaxed) {
+                                return None;
+                            }
+                            let x = x.into_bound(py);
+                            Some(
+                                f.call1(py, (&x,))
+                                    .map_err(|e| notes::add_note(py, e, &name, i, &x)),
+                            )
+                        });
+                        let Some(result) = result else {
+                            return;
+                        };
+                        if result_sender.send((i, result)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }));
+        }
+
+        Pool {
+            jobs: Some(jobs),
+            results: Mutex::new(results),
+            cancelled,
+            workers: handles,
+        }
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.jobs = None;
+        // a worker waiting for the GIL has to get it to see that it's cancelled
+        let workers = std::mem::take(&mut self.workers);
+        Python::with_gil(|py| {
+            py.allow_threads(|| {
+                for worker in workers {
+                    let _ = worker.join();
+                }
+            });
+        });
+    }
+}
+
+pub struct ParMap<S> {
+    iter: S,
+    // dropped before `f`, so that the workers are gone by the time it's released
+    pool: Option<Pool>,
+    // shared with the workers, so that the garbage collector sees every reference to it
+    f: Arc<Py<PyFunction>>,
+    // the name notes give the stage, numbered among the `par_map`s it's built on
+    name: String,
+    workers: usize,
+    chunk_size: usize,
+    ordered: bool,
+    // the number of elements pulled from `iter` so far, and whether it has run out
+    pulled: usize,
+    exhausted: bool,
+    // the number of results yielded so far, which is the index of the next one when ordered
+    yielded: usize,
+    // results by the index of their element, waiting to be yielded
+    done: BTreeMap<usize, PyResult<Py<PyAny>>>,
+}
+
+impl<S> ParMap<S>
+where
+    S: PyIter,
+{
+    pub fn new(
+        iter: S,
+        f: Py<PyFunction>,
+        workers: Option<usize>,
+        ordered: bool,
+        chunk_size: usize,
+    ) -> PyResult<Self> {
+        let workers =
+            workers.unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from));
+        if workers == 0 {
+            return Err(PyValueError::new_err("workers must be at least 1"));
+        }
+        if chunk_size == 0 {
+            return Err(PyValueError::new_err("chunk_size must be at least 1"));
+        }
+
+        Ok(ParMap {
+            name: notes::numbered("par_map", iter.stage_count("par_map")),
+            iter,
+            pool: None,
+            f: Arc::new(f),
+            workers,
+            chunk_size,
+            ordered,
+            pulled: 0,
+            exhausted: false,
+            yielded: 0,
+            done: BTreeMap::new(),
+        })
+    }
+
+    /// The number of elements pulled but not yielded yet, whether mapped or not
+    fn outstanding(&self) -> usize {
+        self.pulled - self.yielded
+    }
+}
+
+impl<S> ParMap<S>
+where
+    S: PyIter,
+{
+    /// Pulls elements `chunk_size` at a time and hands them to the workers, until `chunk_size`
+    /// per worker are pulled but not yielded yet
+    fn top_up(&mut self, py: Python<'_>) {
+        let capacity = self.workers.saturating_mul(self.chunk_size);
+        while !self.exhausted && self.outstanding() < capacity {
+            let n = self.chunk_size.min(capacity - self.outstanding());
+            let mut chunk = Vec::with_capacity(n);
+            for _ in 0..n {
+                match self.iter.next_with(py) {
+                    Some(Ok(x)) => chunk.push((self.pulled, x.unbind())),
+                    Some(Err(e)) => {
+                        self.done.insert(self.pulled, Err(e));
+                    }
+                    None => {
+                        self.exhausted = true;
+                        break;
+                    }
+                }
+                self.pulled += 1;
+            }
+
+            if !chunk.is_empty() {
+                let pool = self
+                    .pool
+                    .get_or_insert_with(|| Pool::new(&self.f, &self.name, self.workers));
+                if let Some(jobs) = &pool.jobs {
+                    // the workers only stop once the pool is dropped
+                    let _ = jobs.send(chunk);
+                }
+            }
+        }
+    }
+
+    /// Takes the next result to yield out of those done, if it's there yet
+    fn ready(&mut self) -> Option<PyResult<Py<PyAny>>> {
+        let result = if self.ordered {
+            self.done.remove(&self.yielded)
+        } else {
+            self.done.pop_first().map(|(_, result)| result)
+        }?;
+        self.yielded += 1;
+        Some(result)
+    }
+}
+
+impl<S> Iterator for ParMap<S>
+where
+    S: PyIter,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let outstanding = self.outstanding();
+        (
+            lower.saturating_add(outstanding),
+            upper.and_then(|upper| upper.checked_add(outstanding)),
+        )
+    }
+}
+
+impl<S> PyIter for ParMap<S>
+where
+    S: PyIter,
+{
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        loop {
+            self.top_up(py);
+            if let Some(result) = self.ready() {
+                return Some(result.map(|x| x.into_bound(py)));
+            }
+            if self.outstanding() == 0 {
+                return None;
+            }
+
+            // every element outstanding but not done is with the workers
+            let results = self.pool.as_mut()?.results.get_mut().ok()?;
+            let (i, result) = py.allow_threads(move || results.recv()).ok()?;
+            self.done.insert(i, result);
+        }
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.iter.traverse(visit)?;
+        visit.call(&*self.f)?;
+        self.done
+            .values()
+            .try_for_each(|x| visit.call(x.as_ref().ok()))
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.iter.close(py)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        usize::from(name == "par_map") + self.iter.stage_count(name)
+    }
+
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("par_map"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("par_map"))
+    }
+}
+
+impl<S> ExactSizeIterator for ParMap<S> where S: PyIter + ExactSizeIterator {}
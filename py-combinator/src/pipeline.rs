@@ -0,0 +1,617 @@
+use std::{any::Any, collections::VecDeque};
+
+use pyo3::{
+    IntoPyObjectExt, PyTraverseError, PyVisit,
+    exceptions::{PyBaseException, PyTypeError},
+    prelude::*,
+    types::{PyFunction, PyList, PyTuple, PyType},
+};
+
+use crate::{
+    base_iterator::PyBaseIteratorT,
+    double_ended_iterator::PyDoubleEndedIteratorT,
+    exact_size_iterator::PyExactSizeIteratorT,
+    notes,
+    py_iter::{PyDoubleEndedIter, PyExactSizeIter, PyIter},
+    sized_double_ended_iterator::PySizedDoubleEndedIteratorT,
+    state,
+};
+
+/// A per-element stage which is fused with its neighbours into a single loop
+pub enum Stage {
+    Map(Py<PyFunction>),
+    Filter(Py<PyFunction>),
+    // the index of the next element
+    Enumerate(usize),
+    // the number of elements still to be let through
+    Take(usize),
+    // the number of elements still to be dropped from the front
+    Skip(usize),
+    // errors matching `types` are replaced by what `handler` returns for them, or dropped
+    Catch {
+        types: Py<PyAny>,
+        handler: Option<Py<PyFunction>>,
+    },
+}
+
+impl Stage {
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        match self {
+            Stage::Map(f) | Stage::Filter(f) => visit.call(f),
+            Stage::Enumerate(_) | Stage::Take(_) | Stage::Skip(_) => Ok(()),
+            Stage::Catch { types, handler } => {
+                visit.call(types)?;
+                visit.call(handler)
+            }
+        }
+    }
+
+    fn clone_ref(&self, py: Python<'_>) -> Self {
+        match self {
+            Stage::Map(f) => Stage::Map(f.clone_ref(py)),
+            Stage::Filter(f) => Stage::Filter(f.clone_ref(py)),
+            Stage::Enumerate(i) => Stage::Enumerate(*i),
+            Stage::Take(n) => Stage::Take(*n),
+            Stage::Skip(n) => Stage::Skip(*n),
+            Stage::Catch { types, handler } => Stage::Catch {
+                types: types.clone_ref(py),
+                handler: handler.as_ref().map(|handler| handler.clone_ref(py)),
+            },
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Stage::Map(_) => "map",
+            Stage::Filter(_) => "filter",
+            Stage::Enumerate(_) => "enumerate",
+            Stage::Take(_) => "take",
+            Stage::Skip(_) => "skip",
+            Stage::Catch { .. } => "catch",
+        }
+    }
+}
+
+/// A stage of a pipeline, numbered among the stages of the same kind
+struct Step {
+    stage: Stage,
+    name: String,
+    // the number of elements given to the stage so far
+    seen: usize,
+}
+
+impl Step {
+    /// Numbers `stage` among the stages of the same kind before it
+    fn new(stage: Stage, before: &[Step]) -> Self {
+        let n = before
+            .iter()
+            .filter(|step| step.stage.name() == stage.name())
+            .count();
+        Step {
+            name: notes::numbered(stage.name(), n),
+            stage,
+            seen: 0,
+        }
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let name = self.stage.name();
+        match &self.stage {
+            Stage::Map(f) | Stage::Filter(f) => (name, self.seen, f).into_bound_py_any(py),
+            Stage::Enumerate(i) | Stage::Take(i) | Stage::Skip(i) => {
+                (name, self.seen, *i).into_bound_py_any(py)
+            }
+            Stage::Catch { types, handler } => {
+                (name, self.seen, types, handler).into_bound_py_any(py)
+            }
+        }
+    }
+
+    fn from_state(saved: &Bound<'_, PyAny>, before: &[Step]) -> PyResult<Self> {
+        let (stage, seen) = match state::tag(saved)?.as_str() {
+            "map" => {
+                let (_, seen, f) = state::fields::<(String, usize, _)>(saved)?;
+                (Stage::Map(f), seen)
+            }
+            "filter" => {
+                let (_, seen, f) = state::fields::<(String, usize, _)>(saved)?;
+                (Stage::Filter(f), seen)
+            }
+            "enumerate" => {
+                let (_, seen, i) = state::fields::<(String, usize, _)>(saved)?;
+                (Stage::Enumerate(i), seen)
+            }
+            "take" => {
+                let (_, seen, n) = state::fields::<(String, usize, _)>(saved)?;
+                (Stage::Take(n), seen)
+            }
+            "skip" => {
+                let (_, seen, n) = state::fields::<(String, usize, _)>(saved)?;
+                (Stage::Skip(n), seen)
+            }
+            "catch" => {
+                let (_, seen, types, handler) =
+                    state::fields::<(String, usize, Bound<'_, PyAny>, _)>(saved)?;
+                exception_types(&types)?;
+                let types = types.unbind();
+                (Stage::Catch { types, handler }, seen)
+            }
+            _ => return Err(state::invalid()),
+        };
+        Ok(Step {
+            seen,
+            ..Step::new(stage, before)
+        })
+    }
+
+    fn clone_ref(&self, py: Python<'_>) -> Self {
+        Step {
+            stage: self.stage.clone_ref(py),
+            name: self.name.clone(),
+            seen: self.seen,
+        }
+    }
+
+    /// Applies the stage to `x`, returning `None` if it's dropped
+    fn apply<'py>(
+        &mut self,
+        py: Python<'py>,
+        x: Bound<'py, PyAny>,
+        remaining: Option<usize>,
+    ) -> Option<PyResult<Bound<'py, PyAny>>> {
+        let index = self.seen;
+        self.seen += 1;
+
+        let result = match &mut self.stage {
+            Stage::Map(f) => f.bind(py).call1((&x,)),
+            Stage::Filter(f) => match f.bind(py).call1((&x,)).and_then(|keep| keep.is_truthy()) {
+                Ok(true) => return Some(Ok(x)),
+                Ok(false) => return None,
+                Err(e) => Err(e),
+            },
+            // enumerate is only offered while every earlier stage maps one element to one, so
+            // the source's remaining length is the index from the back
+            Stage::Enumerate(i) => {
+                return Some(if let Some(remaining) = remaining {
+                    (*i + remaining, x).into_bound_py_any(py)
+                } else {
+                    let x = (*i, x).into_bound_py_any(py);
+                    *i += 1;
+                    x
+                });
+            }
+            // the pipeline stops pulling elements once a take has let through all of its own
+            Stage::Take(n) => {
+                *n -= 1;
+                return Some(Ok(x));
+            }
+            // elements pulled from the back are past those still to be skipped
+            Stage::Skip(n) => {
+                if remaining.is_none() && *n > 0 {
+                    *n -= 1;
+                    return None;
+                }
+                return Some(Ok(x));
+            }
+            Stage::Catch { .. } => return Some(Ok(x)),
+        };
+        Some(result.map_err(|e| notes::add_note(py, e, &self.name, index, &x)))
+    }
+
+    /// Passes on `e` raised by the source or an earlier stage unless the stage catches it,
+    /// returning `None` if it's dropped
+    fn recover<'py>(
+        &mut self,
+        py: Python<'py>,
+        e: PyErr,
+        remaining: Option<usize>,
+    ) -> Option<PyResult<Bound<'py, PyAny>>> {
+        let (types, handler) = match &mut self.stage {
+            Stage::Catch { types, handler } => (types, handler),
+            // an exception counts as one of the elements taken or skipped
+            Stage::Take(n) => {
+                *n -= 1;
+                return Some(Err(e));
+            }
+            Stage::Skip(n) if remaining.is_none() && *n > 0 => {
+                *n -= 1;
+                return None;
+            }
+            _ => return Some(Err(e)),
+        };
+        if !e.is_instance(py, types.bind(py)) {
+            return Some(Err(e));
+        }
+
+        let index = self.seen;
+        self.seen += 1;
+
+        let handler = handler.as_ref()?;
+        let e = e.into_value(py).into_bound(py).into_any();
+        Some(
+            handler
+                .bind(py)
+                .call1((&e,))
+                .map_err(|err| notes::add_note(py, err, &self.name, index, &e)),
+        )
+    }
+}
+
+/// A source followed by the stages applied to each of its elements. Adding a stage to an iterator
+/// that already is a pipeline appends it to the list, so a chain costs one dynamic dispatch per
+/// element however long it is.
+pub struct Pipeline<S> {
+    source: S,
+    stages: Vec<Step>,
+    /// Elements already run through every stage, pulled from the front to find where the takes
+    /// and skips end when pulling from the back of a source of unknown length
+    pulled: VecDeque<PyResult<Py<PyAny>>>,
+}
+
+impl<S> Pipeline<S>
+where
+    S: PipelineSource,
+{
+    /// Appends `stage` to `iter` if it's a pipeline, or starts a new one over it
+    pub fn push(iter: S, stage: Stage) -> Self {
+        let mut pipeline = iter.into_pipeline();
+        let step = Step::new(stage, &pipeline.stages);
+        pipeline.stages.push(step);
+        pipeline
+    }
+}
+
+impl<S> Pipeline<S> {
+    /// Rebuilds a pipeline over `source` from the state of its stages, and the elements it had
+    /// pulled ahead
+    pub fn from_state(
+        source: S,
+        stages: &Bound<'_, PyAny>,
+        pulled: &Bound<'_, PyAny>,
+    ) -> PyResult<Self> {
+        let pulled = pulled.downcast::<PyList>().map_err(|_| state::invalid())?;
+        let mut pipeline = Pipeline {
+            source,
+            stages: Vec::new(),
+            pulled: pulled.iter().map(|x| Ok(x.unbind())).collect(),
+        };
+        for saved in stages.downcast::<PyList>().map_err(|_| state::invalid())? {
+            let step = Step::from_state(&saved, &pipeline.stages)?;
+            pipeline.stages.push(step);
+        }
+        Ok(pipeline)
+    }
+}
+
+impl<S> Pipeline<S>
+where
+    S: PyIter,
+{
+    /// Whether a take has let through all the elements it was asked for, so no more should be
+    /// pulled from the source
+    fn exhausted(&self) -> bool {
+        self.stages
+            .iter()
+            .any(|step| matches!(step.stage, Stage::Take(0)))
+    }
+
+    /// Whether where the takes and skips end can be found from the source's length, as it can
+    /// unless the length is unknown or a stage before one of them may drop elements
+    fn windowed(&self) -> bool {
+        let (lower, upper) = self.source.size_hint();
+        let last = self
+            .stages
+            .iter()
+            .rposition(|step| matches!(step.stage, Stage::Take(_) | Stage::Skip(_)));
+        upper == Some(lower)
+            && last.is_none_or(|last| {
+                !self.stages[..last].iter().any(|step| {
+                    matches!(
+                        step.stage,
+                        Stage::Filter(_) | Stage::Catch { handler: None, .. }
+                    )
+                })
+            })
+    }
+
+    /// The positions among the source's remaining elements of those that make it past every take
+    /// and skip, or `None` without any, for a pipeline that's `windowed`
+    fn window(&self) -> Option<(usize, usize)> {
+        let len = self.source.size_hint().0;
+        self.stages.iter().fold(None, |window, step| {
+            let (start, end) = window.unwrap_or((0, len));
+            match step.stage {
+                Stage::Take(n) => Some((start, end.min(start.saturating_add(n)))),
+                Stage::Skip(n) => Some((start.saturating_add(n).min(end), end)),
+                _ => window,
+            }
+        })
+    }
+
+    /// Pulls from the front until every take has let through all its elements and every skip has
+    /// dropped its own, keeping those that make it through so that the back can be pulled from
+    /// without knowing the source's length. At most the count of the last take is kept.
+    fn pull_ahead(&mut self, py: Python<'_>) {
+        while self
+            .stages
+            .iter()
+            .any(|step| matches!(step.stage, Stage::Take(1..) | Stage::Skip(1..)))
+        {
+            match self.pull(py) {
+                Some(x) => self.pulled.push_back(x.map(Bound::unbind)),
+                None => break,
+            }
+        }
+    }
+
+    /// Pulls the next element of the source that makes it through every stage
+    fn pull<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        loop {
+            if self.exhausted() {
+                return None;
+            }
+            let x = self.source.next_with(py)?;
+            match self.run(py, x, None) {
+                Ok(Some(x)) => return Some(Ok(x)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Runs `x` through every stage, returning `None` if a filter or catch drops it. Errors skip
+    /// every stage until a catch matching them, and are noted with the stage that raised them.
+    /// Elements pulled from the back are numbered by counting the `remaining` ones left in the
+    /// source.
+    fn run<'py>(
+        &mut self,
+        py: Python<'py>,
+        mut x: PyResult<Bound<'py, PyAny>>,
+        mut remaining: Option<usize>,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        for step in &mut self.stages {
+            let next = match x {
+                Ok(x) => step.apply(py, x, remaining),
+                Err(e) => step.recover(py, e, remaining),
+            };
+            match next {
+                Some(next) => x = next,
+                None => return Ok(None),
+            }
+            // the elements still to be skipped never reach the later stages
+            if let (Stage::Skip(n), Some(remaining)) = (&step.stage, &mut remaining) {
+                *remaining = remaining.saturating_sub(*n);
+            }
+        }
+
+        x.map(Some)
+    }
+}
+
+/// Checks that `types` is an exception class or a tuple of them, as an `except` clause does
+pub fn exception_types(types: &Bound<'_, PyAny>) -> PyResult<()> {
+    let is_exception = |x: &Bound<'_, PyAny>| {
+        x.downcast::<PyType>()
+            .is_ok_and(|x| x.is_subclass_of::<PyBaseException>().unwrap_or(false))
+    };
+
+    let valid = match types.downcast::<PyTuple>() {
+        Ok(types) => types.iter().all(|x| is_exception(&x)),
+        Err(_) => is_exception(types),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(PyTypeError::new_err(
+            "catching classes that do not inherit from BaseException is not allowed",
+        ))
+    }
+}
+
+impl<S> Iterator for Pipeline<S>
+where
+    S: PyIter,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) =
+            self.stages
+                .iter()
+                .fold(self.source.size_hint(), |(lower, upper), step| {
+                    match step.stage {
+                        Stage::Filter(_) | Stage::Catch { handler: None, .. } => (0, upper),
+                        Stage::Take(n) => {
+                            (lower.min(n), Some(upper.map_or(n, |upper| upper.min(n))))
+                        }
+                        Stage::Skip(n) => (
+                            lower.saturating_sub(n),
+                            upper.map(|upper| upper.saturating_sub(n)),
+                        ),
+                        _ => (lower, upper),
+                    }
+                });
+        let pulled = self.pulled.len();
+        (
+            lower.saturating_add(pulled),
+            upper.and_then(|upper| upper.checked_add(pulled)),
+        )
+    }
+}
+
+impl<S> PyIter for Pipeline<S>
+where
+    S: PyIter,
+{
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        match self.pulled.pop_front() {
+            Some(x) => Some(x.map(|x| x.into_bound(py))),
+            None => self.pull(py),
+        }
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.source.traverse(visit)?;
+        self.stages
+            .iter()
+            .try_for_each(|step| step.stage.traverse(visit))?;
+        self.pulled.iter().flatten().try_for_each(|x| visit.call(x))
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.source.close(py)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        let own = self
+            .stages
+            .iter()
+            .filter(|step| step.stage.name() == name)
+            .count();
+        own + self.source.stage_count(name)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(Pipeline {
+            source: self.source.try_clone(py)?,
+            stages: self.stages.iter().map(|step| step.clone_ref(py)).collect(),
+            pulled: self
+                .pulled
+                .iter()
+                .map(|x| match x {
+                    Ok(x) => Ok(x.clone_ref(py)),
+                    Err(e) => Err(e.clone_ref(py)),
+                })
+                .collect(),
+        })
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let stages = self
+            .stages
+            .iter()
+            .map(|step| step.state(py))
+            .collect::<PyResult<Vec<_>>>()?;
+        // an exception pulled ahead can't be saved along with the elements around it
+        let pulled = self
+            .pulled
+            .iter()
+            .map(|x| x.as_ref().map_err(|_| state::unsaveable("take")))
+            .collect::<PyResult<Vec<_>>>()?;
+        (
+            "pipeline",
+            self.source.state(py)?,
+            PyList::new(py, stages)?,
+            PyList::new(py, pulled)?,
+        )
+            .into_bound_py_any(py)
+    }
+}
+
+impl<S> DoubleEndedIterator for Pipeline<S>
+where
+    S: PyDoubleEndedIter,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.windowed() {
+            // those of the source past the end of every take are skipped unseen, exceptions
+            // included, and none are left once the back reaches those still to be skipped
+            if let Some((start, end)) = self.window() {
+                if start == end {
+                    return self.pulled.pop_back();
+                }
+                let excess = self.source.size_hint().0 - end;
+                if excess > 0 {
+                    let _ = self.source.nth_back(excess - 1)?;
+                }
+            }
+        } else {
+            // otherwise the elements up to the end of every take are found from the front, so
+            // none of the source's are left for the back
+            Python::with_gil(|py| self.pull_ahead(py));
+            if self.exhausted() {
+                return self.pulled.pop_back();
+            }
+        }
+
+        Python::with_gil(|py| {
+            loop {
+                let Some(x) = self.source.next_back() else {
+                    return self.pulled.pop_back();
+                };
+                let x = x.map(|x| x.into_bound(py));
+                let remaining = self.source.size_hint().0;
+                match self.run(py, x, Some(remaining)) {
+                    Ok(Some(x)) => return Some(Ok(x.unbind())),
+                    Ok(None) => {}
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+        })
+    }
+}
+
+// filters strip the exact size from the iterator classes, so an exact pipeline has none
+impl<S> ExactSizeIterator for Pipeline<S> where S: PyExactSizeIter {}
+
+/// The inner iterator of one of the iterator classes, which may be a pipeline built over an
+/// iterator of the same or a more capable class
+pub trait PipelineSource: PyIter + Sized {
+    fn into_pipeline(self) -> Pipeline<Self>;
+}
+
+macro_rules! impl_pipeline_source {
+    ($ty:ty, [$($from:ty),+]) => {
+        impl PipelineSource for $ty {
+            fn into_pipeline(self) -> Pipeline<Self> {
+                $(
+                    // elements pulled ahead have been through every stage so far, so a pipeline
+                    // holding some is wrapped rather than extended
+                    if (&*self as &dyn Any)
+                        .downcast_ref::<Pipeline<$from>>()
+                        .is_some_and(|pipeline| pipeline.pulled.is_empty())
+                    {
+                        let any: Box<dyn Any> = self;
+                        let Ok(pipeline) = any.downcast::<Pipeline<$from>>() else {
+                            unreachable!()
+                        };
+                        return Pipeline {
+                            source: pipeline.source,
+                            stages: pipeline.stages,
+                            pulled: pipeline.pulled,
+                        };
+                    }
+                )+
+
+                Pipeline {
+                    source: self,
+                    stages: Vec::new(),
+                    pulled: VecDeque::new(),
+                }
+            }
+        }
+    };
+}
+
+impl_pipeline_source!(
+    PyBaseIteratorT,
+    [
+        PyBaseIteratorT,
+        PyDoubleEndedIteratorT,
+        PyExactSizeIteratorT,
+        PySizedDoubleEndedIteratorT
+    ]
+);
+impl_pipeline_source!(
+    PyDoubleEndedIteratorT,
+    [PyDoubleEndedIteratorT, PySizedDoubleEndedIteratorT]
+);
+impl_pipeline_source!(
+    PyExactSizeIteratorT,
+    [PyExactSizeIteratorT, PySizedDoubleEndedIteratorT]
+);
+impl_pipeline_source!(PySizedDoubleEndedIteratorT, [PySizedDoubleEndedIteratorT]);
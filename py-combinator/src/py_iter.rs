@@ -0,0 +1,337 @@
+use std::any::Any;
+use std::iter::Empty;
+use std::vec::IntoIter;
+
+use pyo3::{
+    IntoPyObjectExt, PyTraverseError, PyVisit, exceptions::PyTypeError, prelude::*, types::PyList,
+};
+
+/// An iterator over Python objects which can also be advanced with the GIL already held. Terminal
+/// operations acquire the GIL once and pull every element through `next_with`, so stages that
+/// forward the token down the chain don't acquire it again per element.
+pub trait PyIter: Iterator<Item = PyResult<Py<PyAny>>> + Send + Sync + Any + DynClone {
+    /// Advances the iterator using the caller's GIL token. Stages that don't override it fall
+    /// back on `next`, which acquires the GIL on its own.
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        self.next().map(|x| x.map(|x| x.into_bound(py)))
+    }
+
+    /// Visits every Python object the iterator holds, including those of the iterators it's built
+    /// on, so that the iterator classes can take part in cyclic garbage collection. Iterators
+    /// which hold none keep the default.
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        let _ = visit;
+        Ok(())
+    }
+
+    /// Closes the Python iterators the iterator is built on, such as generators, so that their
+    /// `finally` blocks run as soon as it's no longer needed rather than whenever it's collected.
+    /// Iterators which wrap none keep the default.
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        let _ = py;
+        Ok(())
+    }
+
+    /// Counts the stages named `name` the iterator is built on, so that notes can number a new one
+    /// among them. Iterators which wrap none keep the default.
+    fn stage_count(&self, name: &str) -> usize {
+        let _ = name;
+        0
+    }
+
+    /// Returns a copy of the iterator which advances independently of it, or raises `TypeError`
+    /// through `uncloneable` if a stage of the chain can't be copied without consuming it
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self>
+    where
+        Self: Sized;
+
+    /// Describes the position of the iterator with plain Python objects, which `state::restore`
+    /// rebuilds it from, or raises `TypeError` through `state::unsaveable` if a stage of the
+    /// chain can't be described without consuming it
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>>;
+
+    /// Borrows the iterator as one yielding bound elements within the GIL scope of `py`
+    fn bound<'a, 'py>(&'a mut self, py: Python<'py>) -> BoundIter<'a, 'py, Self>
+    where
+        Self: Sized,
+    {
+        BoundIter {
+            iter: self,
+            py,
+            unchecked: 0,
+        }
+    }
+}
+
+/// Copies an iterator whose type has been erased. Each of the iterator kinds has a method of its
+/// own, as the copy has to be boxed as the same kind.
+pub trait DynClone {
+    fn clone_dyn(&self, py: Python<'_>) -> PyResult<Box<dyn PyIter>>;
+}
+
+impl<T> DynClone for T
+where
+    T: PyIter,
+{
+    fn clone_dyn(&self, py: Python<'_>) -> PyResult<Box<dyn PyIter>> {
+        Ok(Box::new(self.try_clone(py)?))
+    }
+}
+
+pub trait PyDoubleEndedIter: PyIter + DoubleEndedIterator {
+    fn clone_double_ended(&self, py: Python<'_>) -> PyResult<Box<dyn PyDoubleEndedIter>>;
+}
+
+impl<T> PyDoubleEndedIter for T
+where
+    T: PyIter + DoubleEndedIterator,
+{
+    fn clone_double_ended(&self, py: Python<'_>) -> PyResult<Box<dyn PyDoubleEndedIter>> {
+        Ok(Box::new(self.try_clone(py)?))
+    }
+}
+
+pub trait PyExactSizeIter: PyIter + ExactSizeIterator {
+    fn clone_exact_size(&self, py: Python<'_>) -> PyResult<Box<dyn PyExactSizeIter>>;
+}
+
+impl<T> PyExactSizeIter for T
+where
+    T: PyIter + ExactSizeIterator,
+{
+    fn clone_exact_size(&self, py: Python<'_>) -> PyResult<Box<dyn PyExactSizeIter>> {
+        Ok(Box::new(self.try_clone(py)?))
+    }
+}
+
+pub trait PySizedDoubleEndedIter: PyDoubleEndedIter + PyExactSizeIter {
+    fn clone_sized_double_ended(&self, py: Python<'_>)
+    -> PyResult<Box<dyn PySizedDoubleEndedIter>>;
+}
+
+impl<T> PySizedDoubleEndedIter for T
+where
+    T: PyDoubleEndedIter + PyExactSizeIter,
+{
+    fn clone_sized_double_ended(
+        &self,
+        py: Python<'_>,
+    ) -> PyResult<Box<dyn PySizedDoubleEndedIter>> {
+        Ok(Box::new(self.try_clone(py)?))
+    }
+}
+
+/// Copies the boxed iterators of one of the kinds, through the method of that kind
+pub trait BoxClone {
+    fn clone_box(&self, py: Python<'_>) -> PyResult<Box<Self>>;
+}
+
+impl BoxClone for dyn PyIter {
+    fn clone_box(&self, py: Python<'_>) -> PyResult<Box<Self>> {
+        self.clone_dyn(py)
+    }
+}
+
+impl BoxClone for dyn PyDoubleEndedIter {
+    fn clone_box(&self, py: Python<'_>) -> PyResult<Box<Self>> {
+        self.clone_double_ended(py)
+    }
+}
+
+impl BoxClone for dyn PyExactSizeIter {
+    fn clone_box(&self, py: Python<'_>) -> PyResult<Box<Self>> {
+        self.clone_exact_size(py)
+    }
+}
+
+impl BoxClone for dyn PySizedDoubleEndedIter {
+    fn clone_box(&self, py: Python<'_>) -> PyResult<Box<Self>> {
+        self.clone_sized_double_ended(py)
+    }
+}
+
+/// The error raised on copying an iterator built with `stage`, which can't be copied
+pub fn uncloneable(stage: &str) -> PyErr {
+    PyTypeError::new_err(format!("cannot clone an iterator built with {stage}"))
+}
+
+impl<I> PyIter for Box<I>
+where
+    I: PyIter + BoxClone + ?Sized,
+{
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        (**self).next_with(py)
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        (**self).traverse(visit)
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        (**self).close(py)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        (**self).stage_count(name)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        (**self).clone_box(py)
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        (**self).state(py)
+    }
+}
+
+/// The number of elements pulled through a `BoundIter` between checks for signals
+pub const SIGNAL_INTERVAL: usize = 1024;
+
+/// Pulls elements with the GIL held, which keeps the interpreter from running signal handlers on
+/// its own, so `KeyboardInterrupt` and friends are checked for every `SIGNAL_INTERVAL` elements.
+/// A pending one is raised in place of the next element. Terminals take the iterator out of its
+/// object before consuming it, so the object is left empty and the elements pulled so far are
+/// dropped along with the rest.
+pub struct BoundIter<'a, 'py, I> {
+    iter: &'a mut I,
+    py: Python<'py>,
+    // elements pulled since signals were last checked
+    unchecked: usize,
+}
+
+impl<'py, I> BoundIter<'_, 'py, I>
+where
+    I: PyIter,
+{
+    /// Returns the next element, or the exception raised by a signal handler instead of it
+    pub fn next_element(&mut self) -> PyResult<Option<PyResult<Bound<'py, PyAny>>>> {
+        self.unchecked += 1;
+        if self.unchecked == SIGNAL_INTERVAL {
+            self.unchecked = 0;
+            self.py.check_signals()?;
+        }
+        Ok(self.iter.next_with(self.py))
+    }
+}
+
+impl<'py, I> Iterator for BoundIter<'_, 'py, I>
+where
+    I: PyIter,
+{
+    type Item = PyResult<Bound<'py, PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_element().transpose().map(|x| x.and_then(|x| x))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> PyIter for Empty<T>
+where
+    Self: Iterator<Item = PyResult<Py<PyAny>>> + Send + Sync + 'static,
+{
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Ok(std::iter::empty())
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        ("empty",).into_bound_py_any(py)
+    }
+}
+
+/// Copies an element, or the error raised in its place
+pub fn clone_item(py: Python<'_>, item: &PyResult<Py<PyAny>>) -> PyResult<Py<PyAny>> {
+    match item {
+        Ok(x) => Ok(x.clone_ref(py)),
+        Err(e) => Err(e.clone_ref(py)),
+    }
+}
+
+impl PyIter for IntoIter<PyResult<Py<PyAny>>> {
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.as_slice()
+            .iter()
+            .try_for_each(|x| visit.call(x.as_ref().ok()))
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(self
+            .as_slice()
+            .iter()
+            .map(|x| clone_item(py, x))
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+
+    // the elements left over from `take`
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let elements = self
+            .as_slice()
+            .iter()
+            .map(|x| match x {
+                Ok(x) => Ok(x.bind(py)),
+                Err(_) => Err(PyTypeError::new_err(
+                    "cannot save the state of an iterator holding an exception",
+                )),
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        ("elements", PyList::new(py, elements)?).into_bound_py_any(py)
+    }
+}
+
+/// Yields the elements of a double ended iterator in reverse order
+pub struct Rev<S>(pub S);
+
+impl<S> Iterator for Rev<S>
+where
+    S: DoubleEndedIterator,
+{
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<S> DoubleEndedIterator for Rev<S>
+where
+    S: DoubleEndedIterator,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<S> ExactSizeIterator for Rev<S> where S: DoubleEndedIterator + ExactSizeIterator {}
+
+impl<S> PyIter for Rev<S>
+where
+    S: PyDoubleEndedIter,
+{
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.0.traverse(visit)
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.0.close(py)
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        self.0.stage_count(name)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(Rev(self.0.try_clone(py)?))
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        ("rev", self.0.state(py)?).into_bound_py_any(py)
+    }
+}
@@ -0,0 +1,190 @@
+use pyo3::{
+    IntoPyObjectExt, PyTraverseError, PyVisit, exceptions::PyOverflowError, prelude::*,
+    types::PyRange,
+};
+
+use crate::{py_iter::PyIter, state};
+
+enum Bounds {
+    Small { start: i64, step: i64 },
+    // bounds that don't fit in an `i64` are left to Python's integers
+    Big { start: Py<PyAny>, step: Py<PyAny> },
+}
+
+pub struct PyRangeIterator {
+    bounds: Bounds,
+    front: usize,
+    back: usize,
+}
+
+impl PyRangeIterator {
+    /// Returns `None` if the range is too long to be sized
+    #[allow(clippy::similar_names)]
+    pub fn new(range: &Bound<'_, PyRange>) -> PyResult<Option<Self>> {
+        let len = match range.len() {
+            Ok(len) => len,
+            Err(e) if e.is_instance_of::<PyOverflowError>(range.py()) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let start = range.getattr("start")?;
+        let stop = range.getattr("stop")?;
+        let step = range.getattr("step")?;
+
+        let bounds = match (
+            start.extract::<i64>(),
+            stop.extract::<i64>(),
+            step.extract::<i64>(),
+        ) {
+            (Ok(start), Ok(_), Ok(step)) => Bounds::Small { start, step },
+            _ => Bounds::Big {
+                start: start.unbind(),
+                step: step.unbind(),
+            },
+        };
+
+        Ok(Some(PyRangeIterator {
+            bounds,
+            front: 0,
+            back: len,
+        }))
+    }
+
+    /// Rebuilds an iterator over the elements `front..back` of the range from `start` by `step`
+    pub fn from_state(
+        start: &Bound<'_, PyAny>,
+        step: &Bound<'_, PyAny>,
+        front: usize,
+        back: usize,
+    ) -> PyResult<Self> {
+        if front > back {
+            return Err(state::invalid());
+        }
+
+        // as on construction, the bounds are only kept as `i64`s if every element fits in one
+        let last = i128::try_from(back.saturating_sub(1)).unwrap_or(i128::MAX);
+        let bounds = match (start.extract::<i64>(), step.extract::<i64>()) {
+            (Ok(start), Ok(step))
+                if i64::try_from(
+                    i128::from(start).saturating_add(i128::from(step).saturating_mul(last)),
+                )
+                .is_ok() =>
+            {
+                Bounds::Small { start, step }
+            }
+            _ => Bounds::Big {
+                start: start.clone().unbind(),
+                step: step.clone().unbind(),
+            },
+        };
+
+        Ok(PyRangeIterator {
+            bounds,
+            front,
+            back,
+        })
+    }
+
+    fn get<'py>(&self, py: Python<'py>, i: usize) -> PyResult<Bound<'py, PyAny>> {
+        match &self.bounds {
+            Bounds::Small { start, step } => {
+                // every element lies between the bounds, so it fits back into an `i64`
+                #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+                let x = (i128::from(*start) + i128::from(*step) * i as i128) as i64;
+                x.into_bound_py_any(py)
+            }
+            Bounds::Big { start, step } => start.bind(py).add(step.bind(py).mul(i)?),
+        }
+    }
+}
+
+impl Iterator for PyRangeIterator {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front = self.front.saturating_add(n).min(self.back);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for PyRangeIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(Python::with_gil(|py| {
+                self.get(py, self.back).map(Bound::unbind)
+            }))
+        } else {
+            None
+        }
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.back = self.back.saturating_sub(n).max(self.front);
+        self.next_back()
+    }
+}
+
+impl PyIter for PyRangeIterator {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        if self.front < self.back {
+            let item = self.get(py, self.front);
+            self.front += 1;
+            Some(item)
+        } else {
+            None
+        }
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        match &self.bounds {
+            Bounds::Small { .. } => Ok(()),
+            Bounds::Big { start, step } => {
+                visit.call(start)?;
+                visit.call(step)
+            }
+        }
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(PyRangeIterator {
+            bounds: match &self.bounds {
+                Bounds::Small { start, step } => Bounds::Small {
+                    start: *start,
+                    step: *step,
+                },
+                Bounds::Big { start, step } => Bounds::Big {
+                    start: start.clone_ref(py),
+                    step: step.clone_ref(py),
+                },
+            },
+            front: self.front,
+            back: self.back,
+        })
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let (start, step) = match &self.bounds {
+            Bounds::Small { start, step } => {
+                (start.into_bound_py_any(py)?, step.into_bound_py_any(py)?)
+            }
+            Bounds::Big { start, step } => (start.bind(py).clone(), step.bind(py).clone()),
+        };
+        ("range", start, step, self.front, self.back).into_bound_py_any(py)
+    }
+}
+
+impl ExactSizeIterator for PyRangeIterator {
+    fn len(&self) -> usize {
+        self.back - self.front
+    }
+}
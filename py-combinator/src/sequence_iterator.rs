@@ -0,0 +1,239 @@
+use pyo3::{
+    IntoPyObjectExt, PyTraverseError, PyVisit,
+    exceptions::{PyIndexError, PyRuntimeError},
+    prelude::*,
+    types::{PyByteArray, PyBytes, PyList, PyMemoryView, PyString, PyTuple},
+};
+
+use crate::{py_iter::PyIter, state};
+
+enum Sequence {
+    List(Py<PyList>),
+    Tuple(Py<PyTuple>),
+    Bytes(Py<PyBytes>),
+    // str, bytearray, memoryview, or a class relying on the legacy `__getitem__` protocol
+    Other(Py<PyAny>),
+}
+
+impl Sequence {
+    fn as_any<'py>(&self, py: Python<'py>) -> &Bound<'py, PyAny> {
+        match self {
+            Sequence::List(list) => list.bind(py).as_any(),
+            Sequence::Tuple(tuple) => tuple.bind(py).as_any(),
+            Sequence::Bytes(bytes) => bytes.bind(py).as_any(),
+            Sequence::Other(sequence) => sequence.bind(py),
+        }
+    }
+}
+
+/// Iterates over a sequence by position. Unless built from a snapshot, it raises if the
+/// sequence changes size along the way, as the length it reports would no longer hold.
+pub struct PySequenceIterator {
+    sequence: Sequence,
+    initial_len: usize,
+    start: usize,
+    end: usize,
+}
+
+impl PySequenceIterator {
+    /// With `snapshot`, mutable sequences are copied up front and later changes to them are
+    /// not seen
+    pub fn new(sequence: &Bound<'_, PyAny>, snapshot: bool) -> PyResult<Self> {
+        let py = sequence.py();
+        let sequence = if let Ok(tuple) = sequence.downcast::<PyTuple>() {
+            Sequence::Tuple(tuple.clone().unbind())
+        } else if let Ok(bytes) = sequence.downcast::<PyBytes>() {
+            Sequence::Bytes(bytes.clone().unbind())
+        } else if let Ok(list) = sequence.downcast::<PyList>() {
+            if snapshot {
+                Sequence::Tuple(list.to_tuple().unbind())
+            } else {
+                Sequence::List(list.clone().unbind())
+            }
+        } else if snapshot {
+            Sequence::Tuple(
+                PyTuple::new(py, sequence.try_iter()?.collect::<PyResult<Vec<_>>>()?)?.unbind(),
+            )
+        } else {
+            Sequence::Other(sequence.clone().unbind())
+        };
+
+        let len = sequence.as_any(py).len()?;
+
+        Ok(PySequenceIterator {
+            sequence,
+            initial_len: len,
+            start: 0,
+            end: len,
+        })
+    }
+
+    /// Rebuilds an iterator over the positions `start..end` of `sequence`, which had
+    /// `initial_len` elements when iteration began
+    pub fn from_state(
+        sequence: &Bound<'_, PyAny>,
+        initial_len: usize,
+        start: usize,
+        end: usize,
+    ) -> PyResult<Self> {
+        // the positions saved only hold for a sequence of the length iteration began with
+        if start > end || end > initial_len || sequence.len().ok() != Some(initial_len) {
+            return Err(state::invalid());
+        }
+
+        let sequence = if let Ok(tuple) = sequence.downcast::<PyTuple>() {
+            Sequence::Tuple(tuple.clone().unbind())
+        } else if let Ok(bytes) = sequence.downcast::<PyBytes>() {
+            Sequence::Bytes(bytes.clone().unbind())
+        } else if let Ok(list) = sequence.downcast::<PyList>() {
+            Sequence::List(list.clone().unbind())
+        } else {
+            Sequence::Other(sequence.clone().unbind())
+        };
+
+        Ok(PySequenceIterator {
+            sequence,
+            initial_len,
+            start,
+            end,
+        })
+    }
+
+    /// Whether `obj` can be iterated by position: either a builtin sequence indexed in constant
+    /// time, or an object relying on the legacy `__len__`/`__getitem__` protocol without an
+    /// `__iter__` of its own. Other sequences, such as deques, may be slow to index, so they
+    /// are iterated instead.
+    pub fn accepts(obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+        if obj.is_instance_of::<PyList>()
+            || obj.is_instance_of::<PyTuple>()
+            || obj.is_instance_of::<PyString>()
+            || obj.is_instance_of::<PyBytes>()
+            || obj.is_instance_of::<PyByteArray>()
+            || obj.is_instance_of::<PyMemoryView>()
+        {
+            return Ok(true);
+        }
+
+        let ty = obj.get_type();
+        Ok(ty.hasattr("__len__")? && ty.hasattr("__getitem__")? && !ty.hasattr("__iter__")?)
+    }
+
+    fn check_size(&self, py: Python<'_>) -> PyResult<()> {
+        if matches!(self.sequence, Sequence::Tuple(_) | Sequence::Bytes(_)) {
+            return Ok(());
+        }
+
+        let sequence = self.sequence.as_any(py);
+
+        if sequence.len()? == self.initial_len {
+            Ok(())
+        } else {
+            Err(PyRuntimeError::new_err(format!(
+                "{} changed size during iteration",
+                sequence.get_type().name()?
+            )))
+        }
+    }
+
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        backward: bool,
+    ) -> Option<PyResult<Bound<'py, PyAny>>> {
+        if self.start >= self.end {
+            return None;
+        }
+
+        if let Err(e) = self.check_size(py) {
+            self.start = self.end;
+            return Some(Err(e));
+        }
+
+        let i = if backward {
+            self.end -= 1;
+            self.end
+        } else {
+            self.start += 1;
+            self.start - 1
+        };
+        Some(self.get(py, i))
+    }
+
+    fn get<'py>(&self, py: Python<'py>, i: usize) -> PyResult<Bound<'py, PyAny>> {
+        match &self.sequence {
+            Sequence::List(list) => list.bind(py).get_item(i),
+            Sequence::Tuple(tuple) => tuple.bind(py).get_item(i),
+            Sequence::Bytes(bytes) => match bytes.bind(py).as_bytes().get(i) {
+                Some(byte) => byte.into_bound_py_any(py),
+                None => Err(PyIndexError::new_err("index out of range")),
+            },
+            Sequence::Other(sequence) => sequence.bind(py).get_item(i),
+        }
+    }
+}
+
+impl Iterator for PySequenceIterator {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for PySequenceIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.step(py, true).map(|x| x.map(Bound::unbind)))
+    }
+}
+
+impl PyIter for PySequenceIterator {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        self.step(py, false)
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        match &self.sequence {
+            Sequence::List(list) => visit.call(list),
+            Sequence::Tuple(tuple) => visit.call(tuple),
+            Sequence::Bytes(bytes) => visit.call(bytes),
+            Sequence::Other(sequence) => visit.call(sequence),
+        }
+    }
+
+    // a copy of an iterator over a list goes on seeing the changes made to it
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(PySequenceIterator {
+            sequence: match &self.sequence {
+                Sequence::List(list) => Sequence::List(list.clone_ref(py)),
+                Sequence::Tuple(tuple) => Sequence::Tuple(tuple.clone_ref(py)),
+                Sequence::Bytes(bytes) => Sequence::Bytes(bytes.clone_ref(py)),
+                Sequence::Other(sequence) => Sequence::Other(sequence.clone_ref(py)),
+            },
+            initial_len: self.initial_len,
+            start: self.start,
+            end: self.end,
+        })
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        (
+            "sequence",
+            self.sequence.as_any(py),
+            self.initial_len,
+            self.start,
+            self.end,
+        )
+            .into_bound_py_any(py)
+    }
+}
+
+impl ExactSizeIterator for PySequenceIterator {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
@@ -0,0 +1,499 @@
+use pyo3::{IntoPyObjectExt, PyTraverseError, PyVisit, prelude::*, types::PyFunction};
+
+use crate::{
+    any_iterator::AnyIterator,
+    iterators::{PyBaseIterator, PyInfiniteIterator, PySizedDoubleEndedIterator},
+    py_iter::{self, PyIter},
+    state,
+};
+
+pub struct Count {
+    current: Py<PyAny>,
+    step: Py<PyAny>,
+}
+
+impl Count {
+    pub fn new(current: Py<PyAny>, step: Py<PyAny>) -> Self {
+        Count { current, step }
+    }
+}
+
+impl Iterator for Count {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl PyIter for Count {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        Some(
+            self.current
+                .bind(py)
+                .add(self.step.bind(py))
+                .map(|next| std::mem::replace(&mut self.current, next.unbind()).into_bound(py)),
+        )
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.current)?;
+        visit.call(&self.step)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(Count {
+            current: self.current.clone_ref(py),
+            step: self.step.clone_ref(py),
+        })
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        ("count", &self.current, &self.step).into_bound_py_any(py)
+    }
+}
+
+pub struct RepeatN {
+    value: Py<PyAny>,
+    remaining: usize,
+}
+
+impl RepeatN {
+    pub fn new(value: Py<PyAny>, remaining: usize) -> Self {
+        RepeatN { value, remaining }
+    }
+}
+
+impl Iterator for RepeatN {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl DoubleEndedIterator for RepeatN {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.next()
+    }
+}
+
+impl PyIter for RepeatN {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(Ok(self.value.bind(py).clone()))
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.value)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(RepeatN {
+            value: self.value.clone_ref(py),
+            remaining: self.remaining,
+        })
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        ("repeat", &self.value, Some(self.remaining)).into_bound_py_any(py)
+    }
+}
+
+pub struct Repeat {
+    value: Py<PyAny>,
+}
+
+impl Repeat {
+    pub fn new(value: Py<PyAny>) -> Self {
+        Repeat { value }
+    }
+}
+
+impl Iterator for Repeat {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl PyIter for Repeat {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        Some(Ok(self.value.bind(py).clone()))
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.value)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(Repeat {
+            value: self.value.clone_ref(py),
+        })
+    }
+
+    fn state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        ("repeat", &self.value, None::<usize>).into_bound_py_any(py)
+    }
+}
+
+pub struct RepeatWith {
+    f: Py<PyFunction>,
+}
+
+impl Iterator for RepeatWith {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl PyIter for RepeatWith {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        Some(self.f.bind(py).call0())
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.f)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(RepeatWith {
+            f: self.f.clone_ref(py),
+        })
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("repeat_with"))
+    }
+}
+
+pub struct Iterate {
+    // computed as soon as the previous element is yielded, as `std::iter::successors` does, and
+    // `None` once `f` has raised
+    next: Option<PyResult<Py<PyAny>>>,
+    f: Py<PyFunction>,
+}
+
+impl Iterator for Iterate {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.next {
+            Some(Ok(_)) => (usize::MAX, None),
+            Some(Err(_)) => (1, Some(1)),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl PyIter for Iterate {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        let x = self.next.take()?.map(|x| x.into_bound(py));
+        if let Ok(x) = &x {
+            self.next = Some(self.f.bind(py).call1((x,)).map(Bound::unbind));
+        }
+        Some(x)
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(self.next.as_ref().and_then(|x| x.as_ref().ok()))?;
+        visit.call(&self.f)
+    }
+
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        Ok(Iterate {
+            next: self.next.as_ref().map(|x| py_iter::clone_item(py, x)),
+            f: self.f.clone_ref(py),
+        })
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("iterate"))
+    }
+}
+
+pub struct FromFn {
+    f: Py<PyFunction>,
+    sentinel: Option<Py<PyAny>>,
+    done: bool,
+}
+
+impl Iterator for FromFn {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| self.next_with(py).map(|x| x.map(Bound::unbind)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done { (0, Some(0)) } else { (0, None) }
+    }
+}
+
+impl PyIter for FromFn {
+    fn next_with<'py>(&mut self, py: Python<'py>) -> Option<PyResult<Bound<'py, PyAny>>> {
+        if self.done {
+            return None;
+        }
+
+        let x = match self.f.bind(py).call0() {
+            Ok(x) => x,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let stop = match &self.sentinel {
+            Some(sentinel) => x.eq(sentinel.bind(py)),
+            None => Ok(x.is_none()),
+        };
+
+        match stop {
+            Ok(true) => {
+                self.done = true;
+                None
+            }
+            Ok(false) => Some(Ok(x)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        visit.call(&self.f)?;
+        visit.call(&self.sentinel)
+    }
+
+    // `f` keeps its own state between calls, which a copy would share
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("from_fn"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("from_fn"))
+    }
+}
+
+impl ExactSizeIterator for RepeatN {}
+
+pub struct Cycle<S> {
+    source: Option<S>,
+    buffer: Vec<Py<PyAny>>,
+    position: usize,
+}
+
+impl<S> Cycle<S> {
+    pub fn new(source: S) -> Self {
+        Cycle {
+            source: Some(source),
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl<S> Iterator for Cycle<S>
+where
+    S: Iterator<Item = PyResult<Py<PyAny>>>,
+{
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the first pass goes through the source, remembering every element for the later ones
+        if let Some(source) = self.source.as_mut() {
+            match source.next() {
+                Some(Ok(x)) => {
+                    Python::with_gil(|py| self.buffer.push(x.clone_ref(py)));
+                    return Some(Ok(x));
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => self.source = None,
+            }
+        }
+
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let x = Python::with_gil(|py| self.buffer[self.position].clone_ref(py));
+        self.position = (self.position + 1) % self.buffer.len();
+        Some(Ok(x))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let source_empty = self
+            .source
+            .as_ref()
+            .is_none_or(|source| source.size_hint().1 == Some(0));
+
+        match (self.buffer.is_empty(), source_empty) {
+            (true, true) => (0, Some(0)),
+            (false, _) => (usize::MAX, None),
+            (true, false) => (0, None),
+        }
+    }
+}
+
+impl<S> PyIter for Cycle<S>
+where
+    S: PyIter,
+{
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        if let Some(source) = &self.source {
+            source.traverse(visit)?;
+        }
+        self.buffer.iter().try_for_each(|x| visit.call(x))
+    }
+
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.source
+            .as_mut()
+            .map_or(Ok(()), |source| source.close(py))
+    }
+
+    fn stage_count(&self, name: &str) -> usize {
+        self.source
+            .as_ref()
+            .map_or(0, |source| source.stage_count(name))
+    }
+
+    fn try_clone(&self, _py: Python<'_>) -> PyResult<Self> {
+        Err(py_iter::uncloneable("cycle"))
+    }
+
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("cycle"))
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (start, stop = None, step = None))]
+#[doc = "Creates an iterator over the integers of `range(start, stop, step)`, or of
+         `range(start)` if only one bound is given, with a known length and
+         reversal in constant time.
+         
+         Examples:
+             range(5) # [0, 1, 2, 3, 4]
+             range(10, 0, -3) # [10, 7, 4, 1]"]
+#[allow(clippy::similar_names)]
+pub fn range<'py>(
+    py: Python<'py>,
+    start: &Bound<'py, PyAny>,
+    stop: Option<&Bound<'py, PyAny>>,
+    step: Option<&Bound<'py, PyAny>>,
+) -> PyResult<Py<PyAny>> {
+    let builtin_range = py.import("builtins")?.getattr("range")?;
+    let range = match (stop, step) {
+        (None, None) => builtin_range.call1((start,))?,
+        (stop, step) => builtin_range.call1((
+            start,
+            stop,
+            step.map_or(1.into_py_any(py)?, |s| s.clone().unbind()),
+        ))?,
+    };
+    AnyIterator::from_iterable(&range)?.into_py_any(py)
+}
+
+#[pyfunction]
+#[pyo3(signature = (start = None, step = None), text_signature = "(start=0, step=1)")]
+#[doc = "Creates an infinite iterator counting up from `start` in increments of
+         `step`.
+         
+         Examples:
+             count(10, 5) # [10, 15, 20, ...]"]
+pub fn count(
+    py: Python<'_>,
+    start: Option<Py<PyAny>>,
+    step: Option<Py<PyAny>>,
+) -> PyResult<PyInfiniteIterator> {
+    let start = start.map_or_else(|| 0.into_py_any(py), Ok)?;
+    let step = step.map_or_else(|| 1.into_py_any(py), Ok)?;
+
+    // fail on construction rather than on the second element
+    start.bind(py).add(step.bind(py))?;
+    Ok(PyInfiniteIterator::new(Box::new(Count {
+        current: start,
+        step,
+    })))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x, n = None))]
+#[doc = "Creates an iterator yielding `x` over and over again, `n` times if given
+         and forever otherwise.
+         
+         Examples:
+             repeat('a', 3) # ['a', 'a', 'a']
+             repeat('a') # ['a', 'a', 'a', ...]"]
+pub fn repeat(py: Python<'_>, x: Py<PyAny>, n: Option<usize>) -> PyResult<Py<PyAny>> {
+    match n {
+        Some(n) => PySizedDoubleEndedIterator::new(Box::new(RepeatN {
+            value: x,
+            remaining: n,
+        }))
+        .into_py_any(py),
+        None => PyInfiniteIterator::new(Box::new(Repeat { value: x })).into_py_any(py),
+    }
+}
+
+#[pyfunction]
+#[doc = "Creates an infinite iterator yielding the results of calling `f`.
+         
+         Examples:
+             repeat_with(lambda: 1) # [1, 1, 1, ...]"]
+pub fn repeat_with(f: Py<PyFunction>) -> PyInfiniteIterator {
+    PyInfiniteIterator::new(Box::new(RepeatWith { f }))
+}
+
+#[pyfunction]
+#[doc = "Creates an infinite iterator yielding `init`, then every result of
+         applying `f` to the previous element. The iterator stops early if `f`
+         raises.
+         
+         Examples:
+             iterate(1, lambda x: x * 2) # [1, 2, 4, 8, ...]"]
+pub fn iterate(init: Py<PyAny>, f: Py<PyFunction>) -> PyInfiniteIterator {
+    PyInfiniteIterator::new(Box::new(Iterate {
+        next: Some(Ok(init)),
+        f,
+    }))
+}
+
+#[pyfunction]
+#[pyo3(signature = (f, sentinel = None))]
+#[doc = "Creates an iterator yielding the results of calling `f`, stopping once it
+         returns `sentinel`, or `None` if no sentinel is given.
+         
+         Examples:
+             lines = iter(['a', 'b'])
+             from_fn(lambda: next(lines, None)) # ['a', 'b']"]
+pub fn from_fn(f: Py<PyFunction>, sentinel: Option<Py<PyAny>>) -> PyBaseIterator {
+    PyBaseIterator::new(Box::new(FromFn {
+        f,
+        sentinel,
+        done: false,
+    }))
+}
@@ -0,0 +1,164 @@
+use pyo3::{
+    IntoPyObjectExt,
+    exceptions::{PyTypeError, PyValueError},
+    intern,
+    prelude::*,
+    types::{PyList, PyTuple},
+};
+
+use crate::{
+    any_iterator::AnyIterator,
+    pipeline::Pipeline,
+    py_iter::{PyIter, Rev},
+    range_iterator::PyRangeIterator,
+    sequence_iterator::PySequenceIterator,
+    sources::{Count, Repeat, RepeatN},
+};
+
+/// The error raised on saving the state of an iterator built with `stage`, whose progress can't
+/// be described without consuming it
+pub fn unsaveable(stage: &str) -> PyErr {
+    PyTypeError::new_err(format!(
+        "cannot save the state of an iterator built with {stage}"
+    ))
+}
+
+pub fn invalid() -> PyErr {
+    PyValueError::new_err("invalid iterator state")
+}
+
+/// Returns the name a state tuple starts with
+pub fn tag(state: &Bound<'_, PyAny>) -> PyResult<String> {
+    state
+        .downcast::<PyTuple>()
+        .ok()
+        .and_then(|state| state.get_item(0).ok())
+        .and_then(|tag| tag.extract().ok())
+        .ok_or_else(invalid)
+}
+
+/// Extracts the fields of a state tuple, tag included
+pub fn fields<'py, T>(state: &Bound<'py, PyAny>) -> PyResult<T>
+where
+    T: FromPyObject<'py>,
+{
+    state.extract().map_err(|_| invalid())
+}
+
+/// The state of an iterator of the class `kind`, as returned by its `state()`
+pub fn save<'py, I>(py: Python<'py>, kind: &str, iter: &I) -> PyResult<Bound<'py, PyAny>>
+where
+    I: PyIter,
+{
+    (kind, iter.state(py)?).into_bound_py_any(py)
+}
+
+/// What `__reduce__` returns for an iterator in `state`, so that unpickling it calls
+/// `from_state`
+pub fn reduce<'py>(py: Python<'py>, state: Bound<'py, PyAny>) -> PyResult<Bound<'py, PyTuple>> {
+    // pickle finds functions by their module, which is the extension module's full name
+    let from_state = py
+        .import(intern!(py, "py_combinator._py_combinator"))?
+        .getattr(intern!(py, "from_state"))?;
+    PyTuple::new(py, [from_state, PyTuple::new(py, [state])?.into_any()])
+}
+
+/// Rebuilds the inner iterator described by `state`, with every capability it has
+pub fn restore(state: &Bound<'_, PyAny>) -> PyResult<AnyIterator> {
+    Ok(match tag(state)?.as_str() {
+        "sequence" => {
+            let (_, sequence, initial_len, start, end) =
+                fields::<(String, Bound<'_, PyAny>, usize, usize, usize)>(state)?;
+            AnyIterator::SizedDoubleEnded(Box::new(PySequenceIterator::from_state(
+                &sequence,
+                initial_len,
+                start,
+                end,
+            )?))
+        }
+        "range" => {
+            let (_, start, step, front, back) =
+                fields::<(String, Bound<'_, PyAny>, Bound<'_, PyAny>, usize, usize)>(state)?;
+            AnyIterator::SizedDoubleEnded(Box::new(PyRangeIterator::from_state(
+                &start, &step, front, back,
+            )?))
+        }
+        "count" => {
+            let (_, current, step) = fields::<(String, _, _)>(state)?;
+            AnyIterator::Infinite(Box::new(Count::new(current, step)))
+        }
+        "repeat" => match fields::<(String, _, Option<usize>)>(state)? {
+            (_, value, Some(n)) => AnyIterator::SizedDoubleEnded(Box::new(RepeatN::new(value, n))),
+            (_, value, None) => AnyIterator::Infinite(Box::new(Repeat::new(value))),
+        },
+        "elements" => {
+            let (_, elements) = fields::<(String, Bound<'_, PyList>)>(state)?;
+            let elements = elements.iter().map(|x| Ok(x.unbind())).collect::<Vec<_>>();
+            AnyIterator::SizedDoubleEnded(Box::new(elements.into_iter()))
+        }
+        "empty" => AnyIterator::SizedDoubleEnded(Box::new(std::iter::empty())),
+        "rev" => {
+            let (_, inner) = fields::<(String, Bound<'_, PyAny>)>(state)?;
+            match restore(&inner)? {
+                AnyIterator::DoubleEnded(it) => AnyIterator::DoubleEnded(Box::new(Rev(it))),
+                AnyIterator::SizedDoubleEnded(it) => {
+                    AnyIterator::SizedDoubleEnded(Box::new(Rev(it)))
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        "with_length" => {
+            let (_, inner, remaining) = fields::<(String, Bound<'_, PyAny>, usize)>(state)?;
+            restore(&inner)?.trust_length(remaining)
+        }
+        "pipeline" => {
+            let (_, source, stages, pulled) =
+                fields::<(String, Bound<'_, PyAny>, Bound<'_, PyAny>, Bound<'_, PyAny>)>(state)?;
+            match restore(&source)? {
+                AnyIterator::Base(it) => {
+                    AnyIterator::Base(Box::new(Pipeline::from_state(it, &stages, &pulled)?))
+                }
+                AnyIterator::DoubleEnded(it) => {
+                    AnyIterator::DoubleEnded(Box::new(Pipeline::from_state(it, &stages, &pulled)?))
+                }
+                AnyIterator::ExactSize(it) => {
+                    AnyIterator::ExactSize(Box::new(Pipeline::from_state(it, &stages, &pulled)?))
+                }
+                AnyIterator::SizedDoubleEnded(it) => AnyIterator::SizedDoubleEnded(Box::new(
+                    Pipeline::from_state(it, &stages, &pulled)?,
+                )),
+                AnyIterator::Infinite(it) => {
+                    AnyIterator::Infinite(Box::new(Pipeline::from_state(it, &stages, &pulled)?))
+                }
+            }
+        }
+        _ => return Err(invalid()),
+    })
+}
+
+#[pyfunction]
+#[doc = "Rebuilds an iterator from what its `state()` returned, resuming where it
+         left off. Pickled iterators are unpickled through it.
+         
+         Examples:
+             iter # [1, 2, 3]
+             next(iter) # 1
+             from_state(iter.state()) # [2, 3]"]
+pub fn from_state(state: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+    let (kind, inner) = fields::<(String, Bound<'_, PyAny>)>(state)?;
+
+    // the inner iterator may be more capable than the class it was saved from
+    let it = match (kind.as_str(), restore(&inner)?) {
+        ("base", it) => AnyIterator::Base(it.into_base()),
+        ("double_ended", AnyIterator::DoubleEnded(it)) => AnyIterator::DoubleEnded(it),
+        ("double_ended", AnyIterator::SizedDoubleEnded(it)) => AnyIterator::DoubleEnded(it),
+        ("exact_size", AnyIterator::ExactSize(it)) => AnyIterator::ExactSize(it),
+        ("exact_size", AnyIterator::SizedDoubleEnded(it)) => AnyIterator::ExactSize(it),
+        ("sized_double_ended", AnyIterator::SizedDoubleEnded(it)) => {
+            AnyIterator::SizedDoubleEnded(it)
+        }
+        ("infinite", AnyIterator::Infinite(it)) => AnyIterator::Infinite(it),
+        _ => return Err(invalid()),
+    };
+    it.into_py_any(state.py())
+}
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use pyo3::{
+    PyClass, PyTraverseError, PyVisit, exceptions::PyRuntimeError, prelude::*,
+    pyclass_init::PyClassInitializer, sync::MutexExt, types::PyTuple,
+};
+
+use crate::{
+    base_iterator::PyBaseIteratorT,
+    py_iter::{PyIter, clone_item},
+    state,
+};
+
+type TeeSourceT = Box<dyn PyIter>;
+
+struct TeeBuffer {
+    source: Option<TeeSourceT>,
+    exhausted: bool,
+    items: VecDeque<PyResult<Py<PyAny>>>,
+    // absolute index of `items[0]` in the source sequence
+    front: usize,
+    // absolute index of the next item each branch will yield, `None` once dropped
+    positions: Vec<Option<usize>>,
+}
+
+impl TeeBuffer {
+    fn trim(&mut self) {
+        let slowest = self.positions.iter().flatten().min().copied();
+        while self.front < slowest.unwrap_or(usize::MAX) && self.items.pop_front().is_some() {
+            self.front += 1;
+        }
+    }
+}
+
+pub struct TeeIterator {
+    shared: Arc<Mutex<TeeBuffer>>,
+    id: usize,
+}
+
+impl TeeIterator {
+    pub fn split(source: TeeSourceT, n: usize) -> Vec<Self> {
+        let shared = Arc::new(Mutex::new(TeeBuffer {
+            source: Some(source),
+            exhausted: false,
+            items: VecDeque::new(),
+            front: 0,
+            positions: vec![Some(0); n],
+        }));
+
+        (0..n)
+            .map(|id| TeeIterator {
+                shared: Arc::clone(&shared),
+                id,
+            })
+            .collect()
+    }
+
+    /// Locks the shared buffer, which is only poisoned if a branch panicked while holding it
+    fn lock(&self, py: Python<'_>) -> PyResult<MutexGuard<'_, TeeBuffer>> {
+        self.shared
+            .lock_py_attached(py)
+            .map_err(|_| PyRuntimeError::new_err("the tee buffer was poisoned by a panic"))
+    }
+}
+
+/// Splits `source` into `n` branches, each in the iterator class `wrap` builds
+pub fn branches<C>(
+    py: Python<'_>,
+    source: TeeSourceT,
+    n: usize,
+    wrap: fn(PyBaseIteratorT) -> C,
+) -> PyResult<Py<PyTuple>>
+where
+    C: PyClass + Into<PyClassInitializer<C>>,
+{
+    let branches = TeeIterator::split(source, n)
+        .into_iter()
+        .map(|branch| Py::new(py, wrap(Box::new(branch))))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyTuple::new(py, branches)?.unbind())
+}
+
+impl Iterator for TeeIterator {
+    type Item = PyResult<Py<PyAny>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Python::with_gil(|py| {
+            let mut buffer = match self.lock(py) {
+                Ok(buffer) => buffer,
+                Err(e) => return Some(Err(e)),
+            };
+            let position = buffer.positions[self.id]?;
+
+            if position >= buffer.front + buffer.items.len() {
+                if buffer.exhausted {
+                    return None;
+                }
+
+                // the source may run arbitrary Python code, so it's advanced without holding the
+                // lock, a branch re-entering in the meantime finds it missing
+                let Some(mut source) = buffer.source.take() else {
+                    return Some(Err(PyRuntimeError::new_err(
+                        "cannot re-enter the tee iterator",
+                    )));
+                };
+                drop(buffer);
+                let item = source.next();
+                buffer = match self.lock(py) {
+                    Ok(buffer) => buffer,
+                    Err(e) => return Some(Err(e)),
+                };
+                buffer.source = Some(source);
+
+                let Some(item) = item else {
+                    buffer.exhausted = true;
+                    return None;
+                };
+                buffer.items.push_back(item);
+            }
+
+            let item = clone_item(py, &buffer.items[position - buffer.front]);
+            buffer.positions[self.id] = Some(position + 1);
+            buffer.trim();
+            Some(item)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        Python::with_gil(|py| {
+            let Ok(mut buffer) = self.lock(py) else {
+                return (0, None);
+            };
+            let Some(position) = buffer.positions[self.id] else {
+                return (0, Some(0));
+            };
+
+            let buffered = buffer.front + buffer.items.len() - position;
+            if buffer.exhausted {
+                return (buffered, Some(buffered));
+            }
+
+            // as in `next`, the source may run Python code, so it's asked without holding the lock
+            let Some(source) = buffer.source.take() else {
+                return (buffered, None);
+            };
+            drop(buffer);
+            let (lower, upper) = source.size_hint();
+            if let Ok(mut buffer) = self.lock(py) {
+                buffer.source = Some(source);
+            }
+
+            (
+                buffered.saturating_add(lower),
+                upper.and_then(|upper| buffered.checked_add(upper)),
+            )
+        })
+    }
+}
+
+impl PyIter for TeeIterator {
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        // objects shared between branches would be counted once per branch, which the collector
+        // mistakes for references it can't see, so they're only reported by the first open one
+        let Ok(buffer) = self.shared.try_lock() else {
+            return Ok(());
+        };
+        if buffer.positions.iter().position(Option::is_some) != Some(self.id) {
+            return Ok(());
+        }
+
+        if let Some(source) = &buffer.source {
+            source.traverse(visit)?;
+        }
+        buffer
+            .items
+            .iter()
+            .try_for_each(|x| visit.call(x.as_ref().ok()))
+    }
+
+    // a copy is one more branch, starting where this one is
+    fn try_clone(&self, py: Python<'_>) -> PyResult<Self> {
+        let mut buffer = self.lock(py)?;
+        let position = buffer.positions[self.id];
+        buffer.positions.push(position);
+        Ok(TeeIterator {
+            shared: Arc::clone(&self.shared),
+            id: buffer.positions.len() - 1,
+        })
+    }
+
+    // the source is shared, so it's only closed along with the last branch still open
+    fn close(&mut self, py: Python<'_>) -> PyResult<()> {
+        let source = {
+            let mut buffer = self.lock(py)?;
+            buffer.positions[self.id] = None;
+            buffer.trim();
+            if buffer.positions.iter().all(Option::is_none) {
+                buffer.source.take()
+            } else {
+                None
+            }
+        };
+        source.map_or(Ok(()), |mut source| source.close(py))
+    }
+
+    // the buffer is shared with the other branches
+    fn state<'py>(&self, _py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Err(state::unsaveable("tee"))
+    }
+}
+
+impl Drop for TeeIterator {
+    fn drop(&mut self) {
+        Python::with_gil(|py| {
+            if let Ok(mut buffer) = self.lock(py) {
+                buffer.positions[self.id] = None;
+                buffer.trim();
+            }
+        });
+    }
+}
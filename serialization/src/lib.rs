@@ -17,10 +17,32 @@ pub const SELF_GENERIC_ATTRIBUTE: &str = "self_generic";
 pub const SELF_FUNC_ATTRIBUTE: &str = "method_self_arg";
 pub const RETURN_LITERAL_ATTRIBUTE: &str = "return_literal";
 pub const STRIPS_TRAITS_ATTRIBUTE: &str = "strips_traits";
+pub const SIGNATURE_ATTRIBUTE: &str = "signature";
+pub const FALLIBLE_ATTRIBUTE: &str = "fallible";
 pub const EXCLUDE_ATTRIBUTE: &str = "exclude";
+pub const UNBOUNDED_ATTRIBUTE: &str = "unbounded";
 pub const SERIALIZED_METHODS_PATH: &str = "py-combinator/target/iterator_methods.json";
 
 pub const PY_BASE_ITERATOR: &str = "PyBaseIterator";
 pub const PY_DOUBLE_ENDED_ITERATOR: &str = "PyDoubleEndedIterator";
 pub const PY_EXACT_SIZE_ITERATOR: &str = "PyExactSizeIterator";
 pub const PY_SIZED_DOUBLE_ENDED_ITERATOR: &str = "PySizedDoubleEndedIterator";
+pub const PY_INFINITE_ITERATOR: &str = "PyInfiniteIterator";
+pub const PY_ASYNC_ITERATOR: &str = "PyAsyncIterator";
+
+/// The traits behind each iterator class, in the order adapters fall back through them: once some
+/// are stripped, the last one left names the class returned
+pub fn traits_of(iterator: &str) -> Option<&'static [&'static str]> {
+    match iterator {
+        PY_BASE_ITERATOR => Some(&[]),
+        PY_DOUBLE_ENDED_ITERATOR => Some(&[PY_BASE_ITERATOR, PY_DOUBLE_ENDED_ITERATOR]),
+        PY_EXACT_SIZE_ITERATOR => Some(&[PY_BASE_ITERATOR, PY_EXACT_SIZE_ITERATOR]),
+        PY_SIZED_DOUBLE_ENDED_ITERATOR => Some(&[
+            PY_BASE_ITERATOR,
+            PY_EXACT_SIZE_ITERATOR,
+            PY_DOUBLE_ENDED_ITERATOR,
+        ]),
+        PY_INFINITE_ITERATOR => Some(&[PY_BASE_ITERATOR, PY_INFINITE_ITERATOR]),
+        _ => None,
+    }
+}
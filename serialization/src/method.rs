@@ -29,5 +29,7 @@ pub struct Method {
     pub args: Vec<Argument>,
     pub return_type: Option<String>,
     pub literal_return: bool,
+    pub fallible: bool,
     pub strips: Vec<String>,
+    pub signature: Option<String>,
 }
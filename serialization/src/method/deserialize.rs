@@ -1,5 +1,3 @@
-use std::collections::HashMap;
-
 use itertools::Itertools;
 use syn::{Ident, ImplItemFn, parse_str};
 
@@ -9,7 +7,7 @@ use thiserror::Error;
 
 use crate::{
     ImplBlock, Method, PY_BASE_ITERATOR, PY_DOUBLE_ENDED_ITERATOR, PY_EXACT_SIZE_ITERATOR,
-    PY_SIZED_DOUBLE_ENDED_ITERATOR, method::Argument,
+    PY_INFINITE_ITERATOR, PY_SIZED_DOUBLE_ENDED_ITERATOR, method::Argument, traits_of,
 };
 
 #[derive(Debug, Error)]
@@ -108,31 +106,8 @@ fn return_tokens_from(
                 .map_err(|e| MethodDeserializeError::ArgTypeParseError(e.to_string()))?,
         )
     } else {
-        let trait_map: HashMap<_, Vec<_>> = [
-            (PY_BASE_ITERATOR, vec![]),
-            (
-                PY_DOUBLE_ENDED_ITERATOR,
-                vec![PY_BASE_ITERATOR, PY_DOUBLE_ENDED_ITERATOR],
-            ),
-            (
-                PY_EXACT_SIZE_ITERATOR,
-                vec![PY_BASE_ITERATOR, PY_EXACT_SIZE_ITERATOR],
-            ),
-            (
-                PY_SIZED_DOUBLE_ENDED_ITERATOR,
-                vec![
-                    PY_BASE_ITERATOR,
-                    PY_EXACT_SIZE_ITERATOR,
-                    PY_DOUBLE_ENDED_ITERATOR,
-                ],
-            ),
-        ]
-        .into_iter()
-        .collect();
-
-        let available_traits = trait_map
-            .get(injectee_name)
-            .ok_or(MethodDeserializeError::InvalidIteratorName)?;
+        let available_traits =
+            traits_of(injectee_name).ok_or(MethodDeserializeError::InvalidIteratorName)?;
 
         let remaining_traits = available_traits
             .iter()
@@ -159,7 +134,142 @@ fn return_tokens_from(
     }
 }
 
+/// Finds the argument collecting any extra positional arguments, and those only passed by
+/// keyword, as a method's signature lays them out
+fn argument_kinds_from(signature: Option<&str>) -> (Option<String>, Vec<String>) {
+    let Some(signature) = signature else {
+        return (None, Vec::new());
+    };
+
+    let mut entries = signature.split(',').map(str::trim);
+    let varargs = entries
+        .by_ref()
+        .find_map(|e| e.strip_prefix('*').filter(|_| !e.starts_with("**")))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let keyword_only = entries
+        .filter_map(|e| e.split('=').next())
+        .map(|k| k.trim().to_string())
+        .collect();
+
+    (varargs, keyword_only)
+}
+
 impl Method {
+    /// Whether the method builds another iterator rather than consuming this one. Literal methods
+    /// are only counted when they strip traits or return one of the iterator classes.
+    pub fn is_adapter(&self) -> bool {
+        !self.literal_return || !self.strips.is_empty() || self.returned_iterator().is_some()
+    }
+
+    /// The iterator class a literal method always returns, if any
+    pub fn returned_iterator(&self) -> Option<&'static str> {
+        let return_type = self.return_type.as_ref()?;
+        [
+            PY_BASE_ITERATOR,
+            PY_DOUBLE_ENDED_ITERATOR,
+            PY_EXACT_SIZE_ITERATOR,
+            PY_SIZED_DOUBLE_ENDED_ITERATOR,
+            PY_INFINITE_ITERATOR,
+        ]
+        .into_iter()
+        .find(|class| return_type.split_whitespace().any(|t| t == *class))
+    }
+
+    /// The traits an iterator may no longer have after this method, including those missing from
+    /// the class it always returns
+    pub fn stripped_traits(&self) -> Vec<String> {
+        let kept = self.returned_iterator().and_then(traits_of);
+        let mut stripped = self.strips.clone();
+        if let Some(kept) = kept {
+            stripped.extend(
+                [
+                    PY_DOUBLE_ENDED_ITERATOR,
+                    PY_EXACT_SIZE_ITERATOR,
+                    PY_INFINITE_ITERATOR,
+                ]
+                .into_iter()
+                .filter(|t| !kept.contains(t) && !self.strips.iter().any(|s| s == t))
+                .map(str::to_string),
+            );
+        }
+        stripped
+    }
+
+    /// Builds a blueprint method recording a call to this one, along with the trait it needs and
+    /// those it strips, so that the blueprint can reject it before it's ever applied
+    pub fn into_blueprint_item(
+        &self,
+        impl_block: &ImplBlock,
+    ) -> Result<ImplItemFn, MethodDeserializeError> {
+        let self_name = parse_str::<Ident>(&self.name)
+            .map_err(|e| MethodDeserializeError::NamePathParseError(e.to_string()))?;
+        let method_name = &self.name;
+
+        let requires = impl_block
+            .name
+            .last()
+            .ok_or(MethodDeserializeError::EmptyField)?;
+        let strips = self.stripped_traits();
+
+        let typed_args = typed_args_from(&self.args, impl_block)?;
+        let (varargs, keyword_only) = argument_kinds_from(self.signature.as_deref());
+
+        let mut positional = Vec::new();
+        let mut keywords = Vec::new();
+        for name in arg_names_from(&self.args, impl_block)? {
+            let name_string = name.to_string();
+            if keyword_only.contains(&name_string) {
+                keywords.push((name_string, name));
+            } else if varargs.as_ref() != Some(&name_string) {
+                positional.push(name);
+            }
+        }
+        let (keyword_names, keyword_args): (Vec<_>, Vec<_>) = keywords.into_iter().unzip();
+
+        let (args_binding, extra_args) = match varargs {
+            Some(v) => {
+                let v = parse_str::<Ident>(&v)
+                    .map_err(|e| MethodDeserializeError::NameParseError(e.to_string()))?;
+                (
+                    quote! { let mut args },
+                    quote! {
+                        for arg in #v {
+                            args.push(::pyo3::IntoPyObjectExt::into_py_any(arg, py)?);
+                        }
+                    },
+                )
+            }
+            None => (quote! { let args }, quote! {}),
+        };
+
+        let doc_comment = format!("Adds a `{method_name}` step.");
+
+        let signature = match self.signature.as_ref() {
+            Some(s) => {
+                let s: TokenStream2 = parse_str(s)
+                    .map_err(|e| MethodDeserializeError::TokenStreamParseError(e.to_string()))?;
+                quote! { #[pyo3(signature = (#s))] }
+            }
+            None => quote! {},
+        };
+
+        Ok(syn::parse_quote! {
+            #[doc = #doc_comment]
+            #signature
+            pub fn #self_name(&self, py: ::pyo3::Python<'_> #typed_args) -> ::pyo3::PyResult<Self> {
+                #args_binding: ::std::vec::Vec<::pyo3::Py<::pyo3::PyAny>> = ::std::vec![
+                    #(::pyo3::IntoPyObjectExt::into_py_any(#positional, py)?),*
+                ];
+                #extra_args
+                let kwargs: ::std::vec::Vec<(&'static str, ::pyo3::Py<::pyo3::PyAny>)> = ::std::vec![
+                    #((#keyword_names, ::pyo3::IntoPyObjectExt::into_py_any(#keyword_args, py)?)),*
+                ];
+                self.record(py, #method_name, #requires, &[#(#strips),*], args, kwargs)
+            }
+        })
+    }
+
     pub fn into_impl_item(
         &self,
         impl_block: &ImplBlock,
@@ -187,25 +297,48 @@ impl Method {
             None => quote! {},
         };
 
+        let signature = match self.signature.as_ref() {
+            Some(s) => {
+                let s: TokenStream2 = parse_str(s)
+                    .map_err(|e| MethodDeserializeError::TokenStreamParseError(e.to_string()))?;
+                quote! { #[pyo3(signature = (#s))] }
+            }
+            None => quote! {},
+        };
+
+        // the iterator is taken out under its object's lock, so a method called while another
+        // thread is advancing it waits, and one called from the iterator's own callbacks raises
         let impl_item_fn: ImplItemFn = if self.literal_return {
             syn::parse_quote! {
                 #doc_comment
-                pub fn #self_name(&mut self #typed_args) -> #return_type {
-                    #qualified_trait_name :: #self_name (self.#self_function() #call_args)
+                #signature
+                pub fn #self_name(slf: &::pyo3::Bound<'_, Self> #typed_args) -> #return_type {
+                    #qualified_trait_name :: #self_name (slf.get().#self_function()? #call_args)
+                }
+            }
+        } else if self.fallible {
+            syn::parse_quote! {
+                #doc_comment
+                #signature
+                pub fn #self_name(slf: &::pyo3::Bound<'_, Self> #typed_args) -> ::pyo3::PyResult<#return_type> {
+                    ::std::result::Result::Ok(#return_type ::new( ::std::boxed::Box::new ( #qualified_trait_name :: #self_name (slf.get().#self_function()? #call_args)? ) ))
                 }
             }
         } else if return_type.is_empty() {
             syn::parse_quote! {
                 #doc_comment
-                pub fn #self_name(&mut self #typed_args) {
-                    ::std::boxed::Box::new ( #qualified_trait_name :: #self_name (self.#self_function() #call_args) )
+                #signature
+                pub fn #self_name(slf: &::pyo3::Bound<'_, Self> #typed_args) -> ::pyo3::PyResult<()> {
+                    ::std::boxed::Box::new ( #qualified_trait_name :: #self_name (slf.get().#self_function()? #call_args) );
+                    ::std::result::Result::Ok(())
                 }
             }
         } else {
             syn::parse_quote! {
                 #doc_comment
-                pub fn #self_name(&mut self #typed_args) -> #return_type {
-                    #return_type ::new( ::std::boxed::Box::new ( #qualified_trait_name :: #self_name (self.#self_function() #call_args) ) )
+                #signature
+                pub fn #self_name(slf: &::pyo3::Bound<'_, Self> #typed_args) -> ::pyo3::PyResult<#return_type> {
+                    ::std::result::Result::Ok(#return_type ::new( ::std::boxed::Box::new ( #qualified_trait_name :: #self_name (slf.get().#self_function()? #call_args) ) ))
                 }
             }
         };
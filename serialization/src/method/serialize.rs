@@ -5,8 +5,8 @@ use quote::ToTokens;
 use syn::{ImplItemFn, ItemImpl, Meta};
 
 use crate::{
-    AttributeArg, AttributeArgsList, ImplBlock, ImplBlockParseError, Method,
-    RETURN_LITERAL_ATTRIBUTE, STRIPS_TRAITS_ATTRIBUTE, method::Argument,
+    AttributeArg, AttributeArgsList, FALLIBLE_ATTRIBUTE, ImplBlock, ImplBlockParseError, Method,
+    RETURN_LITERAL_ATTRIBUTE, SIGNATURE_ATTRIBUTE, STRIPS_TRAITS_ATTRIBUTE, method::Argument,
 };
 
 impl Method {
@@ -25,6 +25,7 @@ impl Method {
             .collect()
     }
 
+    #[allow(clippy::too_many_lines)]
     pub fn from_item_impl(
         impl_block: &ItemImpl,
         fn_context: &ImplItemFn,
@@ -85,6 +86,12 @@ impl Method {
                 .map(|(func, _)| func.sig.ident.to_string())
                 .collect();
 
+        let fallibles: BTreeSet<_> =
+            ImplBlock::find_method_with_attribute_containing(impl_block, FALLIBLE_ATTRIBUTE)
+                .into_iter()
+                .map(|(func, _)| func.sig.ident.to_string())
+                .collect();
+
         let strips_map: BTreeMap<_, _> =
             ImplBlock::find_method_with_attribute_containing(impl_block, STRIPS_TRAITS_ATTRIBUTE)
                 .into_iter()
@@ -107,6 +114,18 @@ impl Method {
                 })
                 .collect::<Result<_, _>>()?;
 
+        let signature =
+            ImplBlock::find_method_with_attribute_containing(impl_block, SIGNATURE_ATTRIBUTE)
+                .into_iter()
+                .find(|(func, _)| func.sig.ident == fn_context.sig.ident)
+                .map(|(_, attr)| match attr.meta {
+                    Meta::List(l) => Ok(l.tokens.to_string()),
+                    _ => Err(ImplBlockParseError::AttributeParseError(format!(
+                        "expected a parenthesized `{SIGNATURE_ATTRIBUTE}` attribute"
+                    ))),
+                })
+                .transpose()?;
+
         let return_type = match &fn_context.sig.output {
             syn::ReturnType::Default => None,
             syn::ReturnType::Type(_, t) => Some(t.into_token_stream().to_string()),
@@ -114,6 +133,8 @@ impl Method {
 
         let literal_return = literal_returns.iter().contains(&name);
 
+        let fallible = fallibles.iter().contains(&name);
+
         let strips = strips_map.get(&name).unwrap_or(&vec![]).clone();
 
         Ok(Method {
@@ -122,7 +143,9 @@ impl Method {
             args,
             return_type,
             literal_return,
+            fallible,
             strips,
+            signature,
         })
     }
 }